@@ -0,0 +1,61 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct CrtSettings {
+    scanline_intensity: f32,
+    scanline_count: f32,
+    persistence: f32,
+    distortion: f32,
+    chromatic_offset: f32,
+};
+
+// Persistence pass: merge the current frame into the decaying history.
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var history_texture: texture_2d<f32>;
+@group(0) @binding(2) var persistence_sampler: sampler;
+@group(0) @binding(3) var<uniform> persistence_settings: CrtSettings;
+
+@fragment
+fn persistence(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let current = textureSample(screen_texture, persistence_sampler, in.uv);
+    let history = textureSample(history_texture, persistence_sampler, in.uv);
+
+    // Phosphors keep glowing after the beam has passed, but never get brighter than the beam
+    return max(current, history * persistence_settings.persistence);
+}
+
+// Display pass: draw the accumulated image as if on a curved vector monitor.
+
+@group(0) @binding(0) var accumulated_texture: texture_2d<f32>;
+@group(0) @binding(1) var display_sampler: sampler;
+@group(0) @binding(2) var<uniform> display_settings: CrtSettings;
+
+fn barrel(uv: vec2<f32>, strength: f32) -> vec2<f32> {
+    let centered = uv * 2.0 - 1.0;
+    let r2 = dot(centered, centered);
+    return (centered * (1.0 + strength * r2)) * 0.5 + 0.5;
+}
+
+@fragment
+fn display(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let uv = barrel(in.uv, display_settings.distortion);
+
+    if any(uv < vec2(0.0)) || any(uv > vec2(1.0)) {
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
+
+    // Red and blue are pushed apart towards the edges of the screen. Sampled with an explicit
+    // level, because derivatives aren't available after the early return.
+    let offset = (uv - 0.5) * display_settings.chromatic_offset * 2.0;
+    let color = vec4<f32>(
+        textureSampleLevel(accumulated_texture, display_sampler, uv + offset, 0.0).r,
+        textureSampleLevel(accumulated_texture, display_sampler, uv, 0.0).g,
+        textureSampleLevel(accumulated_texture, display_sampler, uv - offset, 0.0).b,
+        1.0,
+    );
+
+    let scanline = 0.5 + 0.5 * cos(uv.y * display_settings.scanline_count * 6.28318530718);
+    let darken = 1.0 - display_settings.scanline_intensity * (1.0 - scanline);
+
+    return vec4(color.rgb * darken, color.a);
+}
@@ -0,0 +1,103 @@
+#import bevy_pbr::mesh_view_bindings::view
+
+// Instanced variant of line_material.wgsl. The mesh holds the segments, while the transform,
+// colour and line width come from the per-instance buffer.
+
+struct VertexInput {
+    @builtin(vertex_index) index: u32,
+    @location(0) position_a: vec3<f32>,
+    @location(1) position_b: vec3<f32>,
+    @location(2) color_a: vec4<f32>,
+    @location(3) color_b: vec4<f32>,
+
+    @location(4) world_from_local_0: vec4<f32>,
+    @location(5) world_from_local_1: vec4<f32>,
+    @location(6) world_from_local_2: vec4<f32>,
+    @location(7) world_from_local_3: vec4<f32>,
+    @location(8) instance_color: vec4<f32>,
+    @location(9) line_width: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+};
+
+const EPSILON: f32 = 4.88e-04;
+
+@vertex
+fn vertex(vertex: VertexInput) -> VertexOutput {
+    var positions = array<vec2<f32>, 6>(
+        vec2(-0.5, 0.),
+        vec2(-0.5, 1.),
+        vec2(0.5, 1.),
+        vec2(-0.5, 0.),
+        vec2(0.5, 1.),
+        vec2(0.5, 0.)
+    );
+    let position = positions[vertex.index % 6];
+
+    let world_from_local = mat4x4<f32>(
+        vertex.world_from_local_0,
+        vertex.world_from_local_1,
+        vertex.world_from_local_2,
+        vertex.world_from_local_3,
+    );
+    let clip_from_local = view.clip_from_world * world_from_local;
+
+    // algorithm based on https://wwwtyro.net/2019/11/18/instanced-lines.html
+    var clip_a = clip_from_local * vec4<f32>(vertex.position_a, 1.0);
+    var clip_b = clip_from_local * vec4<f32>(vertex.position_b, 1.0);
+
+    // Manual near plane clipping to avoid errors when doing the perspective divide inside this shader.
+    clip_a = clip_near_plane(clip_a, clip_b);
+    clip_b = clip_near_plane(clip_b, clip_a);
+    let clip = mix(clip_a, clip_b, position.y);
+
+    let resolution = view.viewport.zw;
+    let screen_a = resolution * (0.5 * clip_a.xy / clip_a.w + 0.5);
+    let screen_b = resolution * (0.5 * clip_b.xy / clip_b.w + 0.5);
+
+    let y_basis = normalize(screen_b - screen_a);
+    let x_basis = vec2(-y_basis.y, y_basis.x);
+
+    var color = mix(vertex.color_a, vertex.color_b, position.y) * vertex.instance_color;
+
+    var line_width = vertex.line_width / clip.w;
+
+    // Line thinness fade from https://acegikmo.com/shapes/docs/#anti-aliasing
+    if line_width > 0.0 && line_width < 1. {
+        color.a *= line_width;
+        line_width = 1.;
+    }
+
+    let x_offset = line_width * position.x * x_basis;
+    let screen = mix(screen_a, screen_b, position.y) + x_offset;
+
+    let clip_position = vec4(clip.w * ((2. * screen) / resolution - 1.), clip.z, clip.w);
+
+    return VertexOutput(clip_position, color);
+}
+
+fn clip_near_plane(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {
+    // Move a if a is behind the near plane and b is in front.
+    if a.z > a.w && b.z <= b.w {
+        // Interpolate a towards b until it's at the near plane.
+        let distance_a = a.z - a.w;
+        let distance_b = b.z - b.w;
+        // Add an epsilon to the interpolator to ensure that the point is
+        // not just behind the clip plane due to floating-point imprecision.
+        let t = distance_a / (distance_a - distance_b) + EPSILON;
+        return mix(a, b, t);
+    }
+    return a;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> FragmentOutput {
+    return FragmentOutput(in.color);
+}
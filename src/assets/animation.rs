@@ -0,0 +1,184 @@
+use bevy::{prelude::*, utils::Duration};
+
+use super::model::Model;
+use crate::instanced_lines::InstancedLines;
+
+/// Name of the model's own lines, usable like any other frame
+pub const REST_FRAME: &str = "rest";
+
+/// Morphs the line mesh of the entity between named frames of its model, e.g. flapping wings
+/// or a turret opening. The entity gets its own mesh, which replaces the one of its [Mesh3d] or
+/// [InstancedLines].
+#[derive(Component, Clone, Debug)]
+pub struct ModelAnimation {
+    pub model: Handle<Model>,
+    /// Frame names in playback order, see [REST_FRAME]
+    pub frames: Vec<String>,
+    /// Time to blend from one frame to the next
+    pub frame_duration: Duration,
+    pub repeat: AnimationRepeat,
+    pub easing: EaseFunction,
+    pub elapsed: Duration,
+    mesh: Option<Handle<Mesh>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimationRepeat {
+    /// Stop at the last frame
+    Once,
+    /// Blend from the last frame back to the first
+    #[default]
+    Loop,
+    /// Play forward, then backward
+    PingPong,
+}
+
+impl ModelAnimation {
+    pub fn new<S: Into<String>>(
+        model: Handle<Model>,
+        frames: impl IntoIterator<Item = S>,
+        frame_duration: Duration,
+    ) -> Self {
+        Self {
+            model,
+            frames: frames.into_iter().map(Into::into).collect(),
+            frame_duration,
+            repeat: default(),
+            easing: EaseFunction::Linear,
+            elapsed: Duration::ZERO,
+            mesh: None,
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: AnimationRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: EaseFunction) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        self.repeat == AnimationRepeat::Once
+            && self.elapsed >= self.frame_duration * self.frames.len().saturating_sub(1) as u32
+    }
+
+    /// Indices into `frames` to blend between, and the blend factor
+    fn sample(&self) -> (usize, usize, f32) {
+        let count = self.frames.len();
+        if count < 2 || self.frame_duration.is_zero() {
+            return (0, 0, 0.);
+        }
+
+        let position = self.elapsed.as_secs_f32() / self.frame_duration.as_secs_f32();
+        let last = (count - 1) as f32;
+
+        let (from, to, t) = match self.repeat {
+            AnimationRepeat::Once => {
+                let position = position.min(last);
+                let from = (position.floor() as usize).min(count - 2);
+                (from, from + 1, position - from as f32)
+            }
+            AnimationRepeat::Loop => {
+                let position = position % count as f32;
+                let from = position.floor() as usize;
+                (from, (from + 1) % count, position.fract())
+            }
+            AnimationRepeat::PingPong => {
+                let mut position = position % (2. * last);
+                if position > last {
+                    position = 2. * last - position;
+                }
+                let from = (position.floor() as usize).min(count - 2);
+                (from, from + 1, position - from as f32)
+            }
+        };
+
+        (from, to, EasingCurve::new(0., 1., self.easing).sample_clamped(t))
+    }
+}
+
+pub fn animate_models(
+    mut query: Query<(&mut ModelAnimation, Option<&mut Mesh3d>, Option<&mut InstancedLines>)>,
+    models: Res<Assets<Model>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+) {
+    for (mut animation, mesh3d, instanced_lines) in query.iter_mut() {
+        // one more update after finishing, to show the last frame
+        let finished = animation.finished();
+        animation.elapsed += time.delta();
+        if finished && animation.mesh.is_some() {
+            continue;
+        }
+
+        let Some(model) = models.get(&animation.model) else {
+            continue;
+        };
+
+        let (from, to, t) = animation.sample();
+        let frame = |index: usize| {
+            let name = animation.frames.get(index)?;
+            if name == REST_FRAME {
+                return None;
+            }
+
+            let frame = model.frame(name);
+            if frame.is_none() {
+                warn_once!("model animation: frame {name} not found");
+            }
+            frame
+        };
+
+        let lines = model.blend(frame(from), frame(to), t);
+
+        let mesh = match &animation.mesh {
+            Some(mesh) => {
+                meshes.insert(mesh, lines.into());
+                mesh.clone()
+            }
+            None => {
+                let mesh = meshes.add(lines);
+                animation.mesh = Some(mesh.clone());
+                mesh
+            }
+        };
+
+        if let Some(mut mesh3d) = mesh3d {
+            if mesh3d.0 != mesh {
+                mesh3d.0 = mesh.clone();
+            }
+        }
+
+        if let Some(mut instanced_lines) = instanced_lines {
+            if instanced_lines.mesh != mesh {
+                instanced_lines.mesh = mesh;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animation_sampling() {
+        let frames = ["rest", "open", "closed"];
+        let mut animation = ModelAnimation::new(default(), frames, Duration::from_secs(1));
+
+        let mut sample_at = |secs: f32, repeat| {
+            animation.elapsed = Duration::from_secs_f32(secs);
+            animation.repeat = repeat;
+            animation.sample()
+        };
+
+        assert_eq!(sample_at(0.5, AnimationRepeat::Once), (0, 1, 0.5));
+        assert_eq!(sample_at(5., AnimationRepeat::Once), (1, 2, 1.));
+        assert_eq!(sample_at(2.5, AnimationRepeat::Loop), (2, 0, 0.5));
+        assert_eq!(sample_at(2.5, AnimationRepeat::PingPong), (1, 2, 0.5));
+        assert_eq!(sample_at(3.75, AnimationRepeat::PingPong), (0, 1, 0.25));
+    }
+}
@@ -0,0 +1,161 @@
+use avian3d::{parry::transformation::try_convex_hull, prelude::*};
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use super::model::Model;
+
+/// Generates a [Collider] from a line model, so the hitbox follows the art. The collider is
+/// inserted once the model is loaded, and regenerated whenever the model changes.
+#[derive(Component, Clone, Debug)]
+pub struct ModelCollider {
+    pub model: Handle<Model>,
+    pub shape: ModelColliderShape,
+}
+
+impl ModelCollider {
+    pub fn new(model: Handle<Model>, shape: ModelColliderShape) -> Self {
+        Self { model, shape }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ModelColliderShape {
+    /// Convex hull around all line endpoints
+    ConvexHull,
+    /// A capsule along every line
+    Capsules { radius: f32 },
+    /// A sphere around the model's origin that contains all line endpoints
+    BoundingCircle,
+}
+
+impl Model {
+    /// Returns None if the shape can't be built, e.g. a convex hull of a single line.
+    pub fn collider(&self, shape: ModelColliderShape) -> Option<Collider> {
+        let points = || self.lines.iter().flat_map(|line| [line.start, line.end]);
+
+        match shape {
+            ModelColliderShape::ConvexHull => {
+                let points: Vec<_> = points().collect();
+                // parry panics on degenerate input instead of returning None
+                let parry_points: Vec<_> = points.iter().map(|&p| p.into()).collect();
+                try_convex_hull(&parry_points).ok()?;
+
+                Collider::convex_hull(points)
+            }
+            ModelColliderShape::Capsules { radius } => {
+                let capsules: Vec<_> = self
+                    .lines
+                    .iter()
+                    .filter(|line| line.start != line.end)
+                    .map(|line| {
+                        (
+                            Vec3::ZERO,
+                            Quat::IDENTITY,
+                            Collider::capsule_endpoints(radius, line.start, line.end),
+                        )
+                    })
+                    .collect();
+
+                (!capsules.is_empty()).then(|| Collider::compound(capsules))
+            }
+            ModelColliderShape::BoundingCircle => {
+                let radius = points().map(Vec3::length).reduce(f32::max)?;
+                Some(Collider::sphere(radius))
+            }
+        }
+    }
+}
+
+pub fn update_model_colliders(
+    query: Query<(Entity, Ref<ModelCollider>, Has<Collider>)>,
+    models: Res<Assets<Model>>,
+    mut events: EventReader<AssetEvent<Model>>,
+    // None for shapes that can't be built, so they are only reported once per model load
+    mut cache: Local<HashMap<AssetId<Model>, Vec<(ModelColliderShape, Option<Collider>)>>>,
+    mut commands: Commands,
+) {
+    let mut changed = vec![];
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            cache.remove(id);
+            changed.push(*id);
+        }
+    }
+
+    for (entity, model_collider, has_collider) in query.iter() {
+        let id = model_collider.model.id();
+
+        if has_collider && !model_collider.is_changed() && !changed.contains(&id) {
+            continue;
+        }
+
+        let Some(model) = models.get(id) else {
+            continue;
+        };
+
+        let colliders = cache.entry(id).or_default();
+        let collider = match colliders
+            .iter()
+            .find(|(shape, _)| *shape == model_collider.shape)
+        {
+            Some((_, collider)) => collider.clone(),
+            None => {
+                let collider = model.collider(model_collider.shape);
+                if collider.is_none() {
+                    warn!("can't build a {:?} collider for {id:?}", model_collider.shape);
+                }
+
+                colliders.push((model_collider.shape, collider.clone()));
+                collider
+            }
+        };
+        let Some(collider) = collider else {
+            continue;
+        };
+
+        commands.entity(entity).insert(collider);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::model::Line;
+
+    fn line(start: Vec3, end: Vec3) -> Line {
+        Line {
+            start,
+            end,
+            color: None,
+            end_color: None,
+        }
+    }
+
+    #[test]
+    fn model_colliders() {
+        let model = Model {
+            lines: vec![line(Vec3::ZERO, Vec3::X * 2.), line(Vec3::X * 2., Vec3::Y)],
+            ..default()
+        };
+
+        let circle = model.collider(ModelColliderShape::BoundingCircle).unwrap();
+        assert_eq!(circle.shape().as_ball().unwrap().radius, 2.);
+
+        let capsules = model
+            .collider(ModelColliderShape::Capsules { radius: 0.1 })
+            .unwrap();
+        assert_eq!(capsules.shape().as_compound().unwrap().shapes().len(), 2);
+
+        assert!(model.collider(ModelColliderShape::ConvexHull).is_some());
+    }
+
+    #[test]
+    fn single_line_has_no_hull() {
+        let model = Model {
+            lines: vec![line(Vec3::ZERO, Vec3::X)],
+            ..default()
+        };
+
+        assert!(model.collider(ModelColliderShape::ConvexHull).is_none());
+    }
+}
@@ -1,12 +1,23 @@
 use bevy::prelude::*;
 
-use self::model::ModelLoader;
+use self::{
+    model::{Model, ModelLoader},
+    svg::SvgLoader,
+};
 
+pub mod animation;
+pub mod collider;
 pub mod model;
+pub mod socket;
+pub mod svg;
 
 pub struct AssetsPlugin;
 impl Plugin for AssetsPlugin {
     fn build(&self, app: &mut App) {
-        app.register_asset_loader(ModelLoader);
+        app.init_asset::<Model>()
+            .register_asset_loader(ModelLoader)
+            .register_asset_loader(SvgLoader)
+            .add_systems(Update, animation::animate_models)
+            .add_systems(PostUpdate, collider::update_model_colliders);
     }
 }
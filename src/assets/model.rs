@@ -1,15 +1,33 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
 use bevy::{asset::AssetLoader, prelude::*, utils::ConditionalSendFuture};
 use serde::{Deserialize, Serialize};
 
+use super::socket::Socket;
 use crate::line_material::LineList;
 
-#[derive(Serialize, Deserialize, TypePath)]
+#[derive(Asset, Serialize, Deserialize, TypePath, Clone, Debug, Default)]
+#[serde(try_from = "ModelFile", into = "ModelFile")]
 pub struct Model {
     pub lines: Vec<Line>,
     pub default_color: Option<Color>,
+    /// Alternative poses of the lines, see [ModelAnimation](super::animation::ModelAnimation)
+    pub frames: Vec<Frame>,
+    /// Named attachment points, see [Sockets](super::socket::Sockets)
+    pub sockets: BTreeMap<String, Socket>,
+}
+
+/// A named pose with the same topology as the model: one `[start, end]` per line.
+#[derive(Serialize, Deserialize, TypePath, Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub name: String,
+    pub lines: Vec<[Vec3; 2]>,
 }
 
-#[derive(Serialize, Deserialize, TypePath)]
+#[derive(Serialize, Deserialize, TypePath, Clone, Debug)]
 pub struct Line {
     pub start: Vec3,
     pub end: Vec3,
@@ -18,49 +36,452 @@ pub struct Line {
     pub end_color: Option<Color>,
 }
 
+/// The on-disk form of a [Model], as found in `mdl.json` files.
+///
+/// Besides the original list of `lines`, a model can have a table of `vertices` that is
+/// referenced by index from `edges`, `strips` and `loops`. All of these can be combined, and
+/// are expanded into plain lines when loading.
+///
+/// `frames` are alternative poses of the model. Each frame lists new positions for the
+/// `lines` and `vertices`, in the same order, while colours and indices are shared.
+///
+/// `sockets` are named attachment points, like `{ "muzzle": { "position": [1, 0, 0] } }`.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ModelFile {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<Line>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vertices: Vec<Vertex>,
+    /// Single segments between two vertices
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<[usize; 2]>,
+    /// Open polylines through the listed vertices
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub strips: Vec<Vec<usize>>,
+    /// Closed polylines, the last vertex connects back to the first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub loops: Vec<Vec<usize>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<FrameFile>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sockets: BTreeMap<String, Socket>,
+    pub default_color: Option<Color>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct FrameFile {
+    pub name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<[Vec3; 2]>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vertices: Vec<Vec3>,
+}
+
+/// Either a bare `[x, y, z]` position, or `{ "position": [x, y, z], "color": ... }`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Vertex {
+    Position(Vec3),
+    Colored { position: Vec3, color: Option<Color> },
+}
+
+impl Vertex {
+    pub fn position(&self) -> Vec3 {
+        match *self {
+            Vertex::Position(position) | Vertex::Colored { position, .. } => position,
+        }
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        match *self {
+            Vertex::Position(_) => None,
+            Vertex::Colored { color, .. } => color,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ModelFileError {
+    #[error("vertex index {index} is out of range, the model has {count} vertices")]
+    VertexOutOfRange { index: usize, count: usize },
+    #[error("frame {frame} has {found} lines, the model has {expected}")]
+    FrameLines {
+        frame: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("frame {frame} has {found} vertices, the model has {expected}")]
+    FrameVertices {
+        frame: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl TryFrom<ModelFile> for Model {
+    type Error = ModelFileError;
+
+    fn try_from(file: ModelFile) -> Result<Self, Self::Error> {
+        let vertex = |index: usize| {
+            file.vertices
+                .get(index)
+                .ok_or(ModelFileError::VertexOutOfRange { index, count: file.vertices.len() })
+        };
+
+        let mut edges = file.edges.clone();
+
+        for strip in file.strips.iter() {
+            edges.extend(strip.array_windows::<2>());
+        }
+
+        for indices in file.loops.iter() {
+            edges.extend(indices.array_windows::<2>());
+
+            if let [first, .., last] = indices[..] {
+                if indices.len() > 2 {
+                    edges.push([last, first]);
+                }
+            }
+        }
+
+        let mut lines = file.lines.clone();
+        for &[a, b] in edges.iter() {
+            let (a, b) = (vertex(a)?, vertex(b)?);
+
+            lines.push(Line {
+                start: a.position(),
+                end: b.position(),
+                color: a.color(),
+                end_color: b.color(),
+            });
+        }
+
+        let frames = file
+            .frames
+            .into_iter()
+            .map(|frame| {
+                if frame.lines.len() != file.lines.len() {
+                    return Err(ModelFileError::FrameLines {
+                        frame: frame.name,
+                        expected: file.lines.len(),
+                        found: frame.lines.len(),
+                    });
+                }
+                if frame.vertices.len() != file.vertices.len() {
+                    return Err(ModelFileError::FrameVertices {
+                        frame: frame.name,
+                        expected: file.vertices.len(),
+                        found: frame.vertices.len(),
+                    });
+                }
+
+                let mut lines = frame.lines;
+                lines.extend(
+                    edges
+                        .iter()
+                        .map(|&[a, b]| [frame.vertices[a], frame.vertices[b]]),
+                );
+
+                Ok(Frame { name: frame.name, lines })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Model {
+            lines,
+            default_color: file.default_color,
+            frames,
+            sockets: file.sockets,
+        })
+    }
+}
+
+impl From<Model> for ModelFile {
+    fn from(model: Model) -> Self {
+        let frames = model
+            .frames
+            .into_iter()
+            .map(|frame| FrameFile {
+                name: frame.name,
+                lines: frame.lines,
+                ..default()
+            })
+            .collect();
+
+        ModelFile {
+            lines: model.lines,
+            frames,
+            sockets: model.sockets,
+            default_color: model.default_color,
+            ..default()
+        }
+    }
+}
+
+impl Model {
+    pub fn validate(&self) -> Result<(), ModelValidationError> {
+        if self.lines.is_empty() {
+            return Err(ModelValidationError::NoLines);
+        }
+
+        for (index, line) in self.lines.iter().enumerate() {
+            if !line.start.is_finite() || !line.end.is_finite() {
+                return Err(ModelValidationError::NonFinite { line: index });
+            }
+        }
+
+        for frame in self.frames.iter() {
+            if let Some(index) = frame
+                .lines
+                .iter()
+                .position(|line| !line.iter().all(|p| p.is_finite()))
+            {
+                return Err(ModelValidationError::NonFinite { line: index });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn frame(&self, name: &str) -> Option<&Frame> {
+        self.frames.iter().find(|frame| frame.name == name)
+    }
+
+    /// Positions of the lines blended between two frames, where `None` is the rest pose
+    pub fn blend(&self, from: Option<&Frame>, to: Option<&Frame>, t: f32) -> LineList {
+        let pose = |frame: Option<&Frame>, index: usize| match frame {
+            Some(frame) => frame.lines[index],
+            None => [self.lines[index].start, self.lines[index].end],
+        };
+
+        let (lines, colors) = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let [from_start, from_end] = pose(from, index);
+                let [to_start, to_end] = pose(to, index);
+                let positions = [from_start.lerp(to_start, t), from_end.lerp(to_end, t)];
+
+                (positions, line.colors(self.default_color))
+            })
+            .unzip();
+
+        LineList { lines, colors }
+    }
+}
+
+impl Model {
+    /// Axis-aligned bounding box of all line endpoints, as (min, max)
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.lines
+            .iter()
+            .flat_map(|line| [line.start, line.end])
+            .fold(None, |bounds, point| match bounds {
+                None => Some((point, point)),
+                Some((min, max)) => Some((min.min(point), max.max(point))),
+            })
+    }
+
+    pub fn apply_settings(&mut self, settings: &ModelSettings) {
+        let center = match settings.recenter {
+            true => self
+                .bounds()
+                .map_or(Vec3::ZERO, |(min, max)| (min + max) / 2.),
+            false => Vec3::ZERO,
+        };
+        let mirror = Vec3::select(settings.mirror, Vec3::NEG_ONE, Vec3::ONE);
+        let transform =
+            |point: Vec3| (point - center) * mirror * settings.scale + settings.translation;
+
+        for line in self.lines.iter_mut() {
+            line.start = transform(line.start);
+            line.end = transform(line.end);
+        }
+
+        for frame in self.frames.iter_mut() {
+            for line in frame.lines.iter_mut() {
+                *line = line.map(transform);
+            }
+        }
+
+        for socket in self.sockets.values_mut() {
+            socket.position = transform(socket.position);
+            socket.direction *= mirror;
+        }
+
+        match settings.color {
+            ModelColor::Keep => {}
+            ModelColor::Override(color) => {
+                for line in self.lines.iter_mut() {
+                    line.color = None;
+                    line.end_color = None;
+                }
+                self.default_color = Some(color);
+            }
+            ModelColor::Tint(tint) => {
+                let tint = tint.to_linear();
+                let multiply = |color: LinearRgba| {
+                    LinearRgba::new(
+                        color.red * tint.red,
+                        color.green * tint.green,
+                        color.blue * tint.blue,
+                        color.alpha * tint.alpha,
+                    )
+                };
+                for line in self.lines.iter_mut() {
+                    let [start, end] = line.colors(self.default_color);
+                    line.color = Some(multiply(start).into());
+                    line.end_color = Some(multiply(end).into());
+                }
+                self.default_color = None;
+            }
+        }
+    }
+}
+
+/// Loader settings to scale, move, mirror and recolour a model. Transforms are applied in the
+/// order recenter, mirror, scale, translate.
+///
+/// Assets are cached by path, so a second `load_with_settings` of a path that is already loaded
+/// returns the first handle with the first settings, and `.meta` files aren't read since the
+/// game uses [AssetMetaCheck::Never](bevy::asset::AssetMetaCheck::Never). For several variants
+/// of one file, load it once, and add a copy with [Model::apply_settings] per variant to
+/// `Assets<Model>`, together with a mesh made from it with `LineList::from`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ModelSettings {
+    pub scale: f32,
+    pub translation: Vec3,
+    /// Move the center of the bounding box to the origin
+    pub recenter: bool,
+    /// Flip the sign of the coordinates along these axes
+    pub mirror: BVec3,
+    pub color: ModelColor,
+}
+
+impl Default for ModelSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.,
+            translation: Vec3::ZERO,
+            recenter: false,
+            mirror: BVec3::FALSE,
+            color: ModelColor::Keep,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ModelColor {
+    #[default]
+    Keep,
+    /// Replace the colour of every line
+    Override(Color),
+    /// Multiply the colour of every line
+    Tint(Color),
+}
+
+impl Line {
+    /// Start and end colour of the line, falling back to the model's default colour
+    pub fn colors(&self, default_color: Option<Color>) -> [LinearRgba; 2] {
+        [
+            self.color.or(default_color).unwrap_or_default().to_linear(),
+            self.end_color
+                .or(self.color)
+                .or(default_color)
+                .unwrap_or_default()
+                .to_linear(),
+        ]
+    }
+}
+
 impl From<Model> for LineList {
     fn from(value: Model) -> Self {
         let (lines, colors) = value
             .lines
             .into_iter()
-            .map(|line| {
-                ([line.start, line.end], [
-                    line.color
-                        .or(value.default_color)
-                        .unwrap_or_default()
-                        .to_linear(),
-                    line.end_color
-                        .or(line.color)
-                        .or(value.default_color)
-                        .unwrap_or_default()
-                        .to_linear(),
-                ])
-            })
+            .map(|line| ([line.start, line.end], line.colors(value.default_color)))
             .unzip();
 
         Self { lines, colors }
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum ModelLoaderError {
+    #[error("failed to read model {}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("failed to parse model {}:{line}:{column}: {source}", path.display())]
+    Json {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        source: serde_json::Error,
+    },
+    #[error("invalid model {}: {source}", path.display())]
+    Invalid {
+        path: PathBuf,
+        source: ModelValidationError,
+    },
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ModelValidationError {
+    #[error("model has no lines")]
+    NoLines,
+    #[error("line {line} has a non-finite coordinate")]
+    NonFinite { line: usize },
+}
+
+/// Parses and validates a model, like the [ModelLoader] does before applying its settings.
+pub fn parse_model(bytes: &[u8], path: &Path) -> Result<Model, ModelLoaderError> {
+    let model: Model = serde_json::from_slice(bytes).map_err(|source| ModelLoaderError::Json {
+        path: path.to_path_buf(),
+        line: source.line(),
+        column: source.column(),
+        source,
+    })?;
+
+    model
+        .validate()
+        .map_err(|source| ModelLoaderError::Invalid { path: path.to_path_buf(), source })?;
+
+    Ok(model)
+}
+
+/// Loads a [Model], with its [Mesh] as a labeled sub-asset, e.g. `models/ship.mdl.json#mesh`.
+/// See [ModelSettings] for the transformations that can be applied while loading.
 pub struct ModelLoader;
 impl AssetLoader for ModelLoader {
-    type Asset = Mesh;
-    type Settings = ();
-    type Error = std::io::Error;
+    type Asset = Model;
+    type Settings = ModelSettings;
+    type Error = ModelLoaderError;
 
     fn load(
         &self,
         reader: &mut dyn bevy::asset::io::Reader,
-        _settings: &Self::Settings,
-        _load_context: &mut bevy::asset::LoadContext,
+        settings: &Self::Settings,
+        load_context: &mut bevy::asset::LoadContext,
     ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
         async move {
             let mut bytes = vec![];
-            reader.read_to_end(&mut bytes).await?;
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(|source| ModelLoaderError::Io {
+                    path: load_context.path().to_path_buf(),
+                    source,
+                })?;
 
-            let model: Model = serde_json::from_slice(&bytes)?;
+            let mut model = parse_model(&bytes, load_context.path())?;
+            model.apply_settings(settings);
 
-            Ok(LineList::from(model).into())
+            let mesh: Mesh = LineList::from(model.clone()).into();
+            load_context.add_labeled_asset("mesh".to_string(), mesh);
+
+            Ok(model)
         }
     }
 
@@ -68,3 +489,159 @@ impl AssetLoader for ModelLoader {
         &["mdl.json"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_has_position() {
+        let source = b"{\n  \"lines\": [\n    { \"start\": [0, 0, 0] \"end\": [1, 0, 0] }\n  ]\n}";
+
+        let err = parse_model(source, Path::new("models/broken.mdl.json")).unwrap_err();
+
+        let ModelLoaderError::Json { line, column, .. } = &err else {
+            panic!("expected a json error, got {err}");
+        };
+        assert_eq!((*line, *column), (3, 26));
+        assert!(err.to_string().contains("models/broken.mdl.json:3:26"));
+    }
+
+    #[test]
+    fn empty_model_is_invalid() {
+        let err = parse_model(b"{ \"lines\": [] }", Path::new("empty.mdl.json")).unwrap_err();
+
+        assert!(matches!(err, ModelLoaderError::Invalid {
+            source: ModelValidationError::NoLines,
+            ..
+        }));
+    }
+
+    #[test]
+    fn indexed_model() {
+        let source = br#"{
+            "vertices": [
+                [0, 0, 0],
+                { "position": [1, 0, 0], "color": { "LinearRgba": { "red": 1, "green": 0, "blue": 0, "alpha": 1 } } },
+                [1, 1, 0],
+                [0, 1, 0]
+            ],
+            "edges": [[0, 2]],
+            "strips": [[0, 1, 2]],
+            "loops": [[0, 1, 3]]
+        }"#;
+
+        let model = parse_model(source, Path::new("indexed.mdl.json")).unwrap();
+        let lines: Vec<_> = model
+            .lines
+            .iter()
+            .map(|line| [line.start, line.end])
+            .collect();
+
+        assert_eq!(lines, vec![
+            [Vec3::ZERO, Vec3::new(1., 1., 0.)],
+            [Vec3::ZERO, Vec3::X],
+            [Vec3::X, Vec3::new(1., 1., 0.)],
+            [Vec3::ZERO, Vec3::X],
+            [Vec3::X, Vec3::Y],
+            [Vec3::Y, Vec3::ZERO],
+        ]);
+        assert_eq!(model.lines[1].end_color, Some(LinearRgba::RED.into()));
+        assert_eq!(model.lines[2].color, Some(LinearRgba::RED.into()));
+    }
+
+    #[test]
+    fn vertex_out_of_range() {
+        let source = br#"{ "vertices": [[0, 0, 0]], "edges": [[0, 1]] }"#;
+
+        let err = parse_model(source, Path::new("broken.mdl.json")).unwrap_err();
+
+        assert!(err.to_string().contains("vertex index 1 is out of range"), "{err}");
+    }
+
+    #[test]
+    fn model_settings() {
+        let source = br#"{ "lines": [{ "start": [0, 0, 0], "end": [2, 4, 0], "color": { "LinearRgba": { "red": 1, "green": 1, "blue": 1, "alpha": 1 } } }] }"#;
+        let mut model = parse_model(source, Path::new("settings.mdl.json")).unwrap();
+
+        model.apply_settings(&ModelSettings {
+            scale: 2.,
+            translation: Vec3::Z,
+            recenter: true,
+            mirror: BVec3::new(true, false, false),
+            color: ModelColor::Tint(LinearRgba::RED.into()),
+        });
+
+        let line = &model.lines[0];
+        assert_eq!([line.start, line.end], [Vec3::new(2., -4., 1.), Vec3::new(-2., 4., 1.)]);
+        assert_eq!(line.colors(None), [LinearRgba::RED; 2]);
+    }
+
+    #[test]
+    fn model_frames() {
+        let source = br#"{
+            "lines": [{ "start": [0, 0, 0], "end": [1, 0, 0] }],
+            "vertices": [[0, 0, 0], [0, 1, 0]],
+            "edges": [[0, 1]],
+            "frames": [{ "name": "open", "lines": [[[0, 0, 0], [3, 0, 0]]], "vertices": [[0, 0, 0], [0, 3, 0]] }]
+        }"#;
+
+        let model = parse_model(source, Path::new("frames.mdl.json")).unwrap();
+
+        let open = model.frame("open").unwrap();
+        assert_eq!(open.lines, vec![[Vec3::ZERO, Vec3::X * 3.], [Vec3::ZERO, Vec3::Y * 3.]]);
+
+        let blended = model.blend(None, Some(open), 0.5);
+        assert_eq!(blended.lines, vec![[Vec3::ZERO, Vec3::X * 2.], [Vec3::ZERO, Vec3::Y * 2.]]);
+    }
+
+    #[test]
+    fn frame_topology_mismatch() {
+        let source = br#"{ "vertices": [[0, 0, 0], [0, 1, 0]], "edges": [[0, 1]], "frames": [{ "name": "broken", "vertices": [[0, 0, 0]] }] }"#;
+
+        let err = parse_model(source, Path::new("broken.mdl.json")).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("frame broken has 1 vertices, the model has 2"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn model_sockets() {
+        let source = br#"{
+            "lines": [{ "start": [0, 0, 0], "end": [1, 0, 0] }],
+            "sockets": { "muzzle": { "position": [1, 1, 0] }, "engine": { "position": [0, 0, 0], "direction": [-1, 0, 0] } }
+        }"#;
+        let mut model = parse_model(source, Path::new("sockets.mdl.json")).unwrap();
+
+        model.apply_settings(&ModelSettings {
+            mirror: BVec3::new(false, true, false),
+            ..default()
+        });
+
+        assert_eq!(model.sockets["muzzle"], Socket {
+            position: Vec3::new(1., -1., 0.),
+            direction: Vec3::X
+        });
+        assert_eq!(model.sockets["engine"].direction, Vec3::NEG_X);
+    }
+
+    #[test]
+    fn bundled_models() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/models");
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(".mdl.json") {
+                continue;
+            }
+
+            let bytes = std::fs::read(&path).unwrap();
+            if let Err(err) = parse_model(&bytes, &path) {
+                panic!("{err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::model::Model;
+
+/// A named attachment point of a model, e.g. a gun muzzle or an engine.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Socket {
+    pub position: Vec3,
+    /// Forward direction of the socket, which is the model's forward (+X) by default
+    #[serde(default = "Socket::default_direction")]
+    pub direction: Vec3,
+}
+
+impl Socket {
+    fn default_direction() -> Vec3 {
+        Vec3::X
+    }
+
+    /// Transform relative to the model, with its local +X axis pointing along `direction`
+    pub fn transform(&self) -> Transform {
+        let direction = self.direction.normalize_or(Vec3::X);
+        Transform::from_translation(self.position)
+            .with_rotation(Quat::from_rotation_arc(Vec3::X, direction))
+    }
+}
+
+/// Exposes the sockets of a model on the entity, to be queried with [Sockets].
+#[derive(Component, Clone, Debug)]
+pub struct SocketModel(pub Handle<Model>);
+
+/// World transforms of the sockets of entities with a [SocketModel].
+#[derive(SystemParam)]
+pub struct Sockets<'w, 's> {
+    models: Res<'w, Assets<Model>>,
+    query: Query<'w, 's, (&'static SocketModel, &'static GlobalTransform)>,
+}
+
+impl Sockets<'_, '_> {
+    /// None if the entity has no [SocketModel], the model isn't loaded, or it has no such socket
+    pub fn world_transform(&self, entity: Entity, name: &str) -> Option<GlobalTransform> {
+        let (SocketModel(model), transform) = self.query.get(entity).ok()?;
+        let socket = self.models.get(model)?.sockets.get(name)?;
+
+        Some(transform.mul_transform(socket.transform()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn socket_world_transform() {
+        let mut world = World::new();
+
+        let mut model = Model::default();
+        model.sockets.insert("muzzle".into(), Socket {
+            position: Vec3::new(1., 0.5, 0.),
+            direction: Vec3::Y,
+        });
+        let mut models = Assets::<Model>::default();
+        let model = models.add(model);
+        world.insert_resource(models);
+
+        let transform =
+            Transform::from_xyz(10., 0., 0.).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        let ship = world
+            .spawn((SocketModel(model), GlobalTransform::from(transform)))
+            .id();
+
+        let mut state = bevy::ecs::system::SystemState::<Sockets>::new(&mut world);
+        let sockets = state.get(&world);
+
+        let muzzle = sockets.world_transform(ship, "muzzle").unwrap();
+        assert!(muzzle
+            .translation()
+            .abs_diff_eq(Vec3::new(9.5, 1., 0.), 1e-5));
+        assert!(muzzle.right().abs_diff_eq(Vec3::NEG_X, 1e-5));
+        assert!(sockets.world_transform(ship, "engine").is_none());
+    }
+}
@@ -0,0 +1,709 @@
+use std::{
+    f32::consts::TAU,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::AssetLoader, math::Affine2, prelude::*, utils::ConditionalSendFuture};
+use serde::{Deserialize, Serialize};
+
+use super::model::{Line, Model, ModelSettings, ModelValidationError};
+use crate::line_material::LineList;
+
+/// Settings of the [SvgLoader]. SVG coordinates are y-down, they are mirrored to y-up before
+/// `model` is applied.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SvgSettings {
+    /// Maximum distance between a curve and its flattened lines, in SVG user units
+    pub tolerance: f32,
+    pub model: ModelSettings,
+}
+
+impl Default for SvgSettings {
+    fn default() -> Self {
+        Self { tolerance: 0.5, model: default() }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SvgLoaderError {
+    #[error("failed to read svg {}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("failed to parse svg {}: {source}", path.display())]
+    Xml { path: PathBuf, source: roxmltree::Error },
+    #[error("invalid svg {}: <{element}> {message}", path.display())]
+    Element {
+        path: PathBuf,
+        element: String,
+        message: String,
+    },
+    #[error("invalid svg {}: {source}", path.display())]
+    Invalid {
+        path: PathBuf,
+        source: ModelValidationError,
+    },
+}
+
+/// Stroke of an element, inherited by its children. As in SVG, there is none by default.
+#[derive(Clone, Copy)]
+enum Stroke {
+    None,
+    Color(Color),
+}
+
+/// A flattened subpath, before the element's transform is applied
+struct Polyline {
+    points: Vec<Vec2>,
+    closed: bool,
+}
+
+fn parse_svg(source: &str, tolerance: f32, path: &Path) -> Result<Model, SvgLoaderError> {
+    let document = roxmltree::Document::parse(source)
+        .map_err(|source| SvgLoaderError::Xml { path: path.to_path_buf(), source })?;
+
+    let mut lines = vec![];
+    collect_lines(document.root_element(), Affine2::IDENTITY, Stroke::None, tolerance, &mut lines)
+        .map_err(|(element, message)| SvgLoaderError::Element {
+            path: path.to_path_buf(),
+            element,
+            message,
+        })?;
+
+    let model = Model { lines, ..default() };
+    model
+        .validate()
+        .map_err(|source| SvgLoaderError::Invalid { path: path.to_path_buf(), source })?;
+
+    Ok(model)
+}
+
+/// Attribute of the element, either set directly or through its `style`
+fn attribute<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    let style = node.attribute("style").and_then(|style| {
+        style.split(';').find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            (key.trim() == name).then_some(value.trim())
+        })
+    });
+
+    style.or_else(|| node.attribute(name))
+}
+
+fn collect_lines(
+    node: roxmltree::Node,
+    parent_transform: Affine2,
+    parent_stroke: Stroke,
+    tolerance: f32,
+    lines: &mut Vec<Line>,
+) -> Result<(), (String, String)> {
+    let name = node.tag_name().name();
+    let error = |message: String| (name.to_string(), message);
+
+    if matches!(name, "defs" | "clipPath" | "mask" | "marker" | "pattern" | "symbol")
+        || attribute(node, "display") == Some("none")
+    {
+        return Ok(());
+    }
+
+    let transform = match node.attribute("transform") {
+        Some(transform) => parent_transform * parse_transform(transform).map_err(error)?,
+        None => parent_transform,
+    };
+
+    let stroke = match attribute(node, "stroke") {
+        None | Some("inherit") => parent_stroke,
+        Some("none") => Stroke::None,
+        Some(color) => Stroke::Color(parse_color(color).map_err(error)?),
+    };
+
+    let number = |attribute: &str| -> Result<f32, (String, String)> {
+        node.attribute(attribute).map_or(Ok(0.), |value| {
+            value
+                .trim_end_matches("px")
+                .parse()
+                .map_err(|_| error(format!("{attribute} is not a number: {value}")))
+        })
+    };
+
+    let polylines = match name {
+        "path" => parse_path(node.attribute("d").unwrap_or_default(), tolerance).map_err(error)?,
+        "line" => vec![Polyline {
+            points: vec![
+                Vec2::new(number("x1")?, number("y1")?),
+                Vec2::new(number("x2")?, number("y2")?),
+            ],
+            closed: false,
+        }],
+        "polyline" | "polygon" => {
+            let mut lexer = Lexer::new(node.attribute("points").unwrap_or_default());
+            let mut points = vec![];
+            while lexer.has_number() {
+                points.push(lexer.point().map_err(error)?);
+            }
+
+            vec![Polyline { points, closed: name == "polygon" }]
+        }
+        "rect" => {
+            let (x, y) = (number("x")?, number("y")?);
+            let (width, height) = (number("width")?, number("height")?);
+
+            vec![Polyline {
+                points: vec![
+                    Vec2::new(x, y),
+                    Vec2::new(x + width, y),
+                    Vec2::new(x + width, y + height),
+                    Vec2::new(x, y + height),
+                ],
+                closed: true,
+            }]
+        }
+        "circle" | "ellipse" => {
+            let center = Vec2::new(number("cx")?, number("cy")?);
+            let radius = match name {
+                "circle" => Vec2::splat(number("r")?),
+                _ => Vec2::new(number("rx")?, number("ry")?),
+            };
+
+            let mut points = arc_points(center, radius, 0., 0., TAU, tolerance);
+            points.pop();
+
+            vec![Polyline { points, closed: true }]
+        }
+        _ => vec![],
+    };
+
+    let color = match stroke {
+        Stroke::None => None,
+        Stroke::Color(color) => Some(color),
+    };
+
+    for polyline in polylines.into_iter().filter(|_| color.is_some()) {
+        let points: Vec<_> = polyline
+            .points
+            .iter()
+            .map(|&point| transform.transform_point2(point))
+            .collect();
+
+        let mut segments: Vec<_> = points.array_windows::<2>().copied().collect();
+        if let [first, .., last] = points[..] {
+            if polyline.closed && first != last {
+                segments.push([last, first]);
+            }
+        }
+
+        lines.extend(
+            segments
+                .into_iter()
+                .filter(|[a, b]| a != b)
+                .map(|[a, b]| Line {
+                    // SVG is y-down
+                    start: Vec3::new(a.x, -a.y, 0.),
+                    end: Vec3::new(b.x, -b.y, 0.),
+                    color,
+                    end_color: None,
+                }),
+        );
+    }
+
+    children(node, transform, stroke, tolerance, lines)
+}
+
+fn children(
+    node: roxmltree::Node,
+    transform: Affine2,
+    stroke: Stroke,
+    tolerance: f32,
+    lines: &mut Vec<Line>,
+) -> Result<(), (String, String)> {
+    for child in node.children().filter(roxmltree::Node::is_element) {
+        collect_lines(child, transform, stroke, tolerance, lines)?;
+    }
+
+    Ok(())
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    if let Some(hex) = value.strip_prefix('#') {
+        return Srgba::hex(hex)
+            .map(Color::from)
+            .map_err(|err| format!("invalid colour {value}: {err}"));
+    }
+
+    if let Some(components) = value
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let mut lexer = Lexer::new(components);
+        let [r, g, b] = [lexer.number()?, lexer.number()?, lexer.number()?];
+        return Ok(Color::srgb_u8(r as u8, g as u8, b as u8));
+    }
+
+    match value {
+        "black" => Ok(Color::BLACK),
+        "white" => Ok(Color::WHITE),
+        "red" => Ok(Color::srgb(1., 0., 0.)),
+        "lime" => Ok(Color::srgb(0., 1., 0.)),
+        "blue" => Ok(Color::srgb(0., 0., 1.)),
+        "yellow" => Ok(Color::srgb(1., 1., 0.)),
+        "cyan" | "aqua" => Ok(Color::srgb(0., 1., 1.)),
+        "magenta" | "fuchsia" => Ok(Color::srgb(1., 0., 1.)),
+        _ => Err(format!("unsupported colour {value}")),
+    }
+}
+
+fn parse_transform(value: &str) -> Result<Affine2, String> {
+    let mut transform = Affine2::IDENTITY;
+
+    for function in value.split_inclusive(')') {
+        let Some((name, arguments)) = function.split_once('(') else {
+            continue;
+        };
+
+        let mut lexer = Lexer::new(arguments.trim_end_matches(')'));
+        let mut args = vec![];
+        while lexer.has_number() {
+            args.push(lexer.number()?);
+        }
+
+        let next = match (name.trim_matches(|c: char| c.is_whitespace() || c == ','), &args[..]) {
+            ("matrix", &[a, b, c, d, e, f]) => Affine2::from_cols_array(&[a, b, c, d, e, f]),
+            ("translate", &[x]) => Affine2::from_translation(Vec2::new(x, 0.)),
+            ("translate", &[x, y]) => Affine2::from_translation(Vec2::new(x, y)),
+            ("scale", &[s]) => Affine2::from_scale(Vec2::splat(s)),
+            ("scale", &[x, y]) => Affine2::from_scale(Vec2::new(x, y)),
+            ("rotate", &[angle]) => Affine2::from_angle(angle.to_radians()),
+            ("rotate", &[angle, x, y]) => {
+                let center = Vec2::new(x, y);
+                Affine2::from_translation(center)
+                    * Affine2::from_angle(angle.to_radians())
+                    * Affine2::from_translation(-center)
+            }
+            ("skewX", &[angle]) => {
+                Affine2::from_cols_array(&[1., 0., angle.to_radians().tan(), 1., 0., 0.])
+            }
+            ("skewY", &[angle]) => {
+                Affine2::from_cols_array(&[1., angle.to_radians().tan(), 0., 1., 0., 0.])
+            }
+            (name, _) => return Err(format!("unsupported transform {name}({arguments}")),
+        };
+
+        transform *= next;
+    }
+
+    Ok(transform)
+}
+
+/// Splits path data and point lists into commands and numbers. Numbers may be separated by
+/// whitespace, commas, or nothing at all, e.g. `1-2.5.5` is `1 -2.5 0.5`.
+struct Lexer<'a> {
+    source: &'a str,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source }
+    }
+
+    fn skip_separators(&mut self) {
+        self.source = self
+            .source
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        self.source
+            .starts_with(|c: char| c.is_ascii_digit() || matches!(c, '-' | '+' | '.'))
+    }
+
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let command = self.source.chars().next()?;
+        self.source = &self.source[command.len_utf8()..];
+        Some(command)
+    }
+
+    fn number(&mut self) -> Result<f32, String> {
+        self.skip_separators();
+
+        let bytes = self.source.as_bytes();
+        let mut end = 0;
+        let mut seen_dot = false;
+        let mut seen_exponent = false;
+
+        while end < bytes.len() {
+            match bytes[end] {
+                b'0'..=b'9' => {}
+                b'-' | b'+' if end == 0 || matches!(bytes[end - 1], b'e' | b'E') => {}
+                b'.' if !seen_dot && !seen_exponent => seen_dot = true,
+                b'e' | b'E' if !seen_exponent && end > 0 => seen_exponent = true,
+                _ => break,
+            }
+            end += 1;
+        }
+
+        let (number, rest) = self.source.split_at(end);
+        self.source = rest;
+        number
+            .parse()
+            .map_err(|_| format!("expected a number, found {:?}", number))
+    }
+
+    fn point(&mut self) -> Result<Vec2, String> {
+        Ok(Vec2::new(self.number()?, self.number()?))
+    }
+
+    /// Arc flags are single digits that don't need a separator
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.source.as_bytes().first() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(format!("expected an arc flag, found {:?}", self.source)),
+        };
+        self.source = &self.source[1..];
+        Ok(flag)
+    }
+}
+
+fn parse_path(data: &str, tolerance: f32) -> Result<Vec<Polyline>, String> {
+    let mut lexer = Lexer::new(data);
+    let mut polylines = vec![];
+    let mut current = Polyline { points: vec![], closed: false };
+
+    let mut position = Vec2::ZERO;
+    let mut start = Vec2::ZERO;
+    // the second control point of the previous curve, for the smooth S and T commands
+    let mut last_control: Option<(char, Vec2)> = None;
+    let mut command = None;
+
+    loop {
+        let next = match lexer.has_number() {
+            // repeated arguments reuse the previous command, or L after a M
+            true => match command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some(command) => command,
+                None => return Err("path data must start with a command".to_string()),
+            },
+            false => match lexer.command() {
+                Some(command) => command,
+                None => break,
+            },
+        };
+        command = Some(next);
+
+        let relative = next.is_ascii_lowercase();
+        let offset = if relative { position } else { Vec2::ZERO };
+        let mut control = None;
+
+        match next.to_ascii_uppercase() {
+            'M' => {
+                let previous =
+                    std::mem::replace(&mut current, Polyline { points: vec![], closed: false });
+                if previous.points.len() > 1 {
+                    polylines.push(previous);
+                }
+                position = lexer.point()? + offset;
+                start = position;
+                current.points = vec![position];
+            }
+            'L' => {
+                position = lexer.point()? + offset;
+                current.points.push(position);
+            }
+            'H' => {
+                position.x = lexer.number()? + offset.x;
+                current.points.push(position);
+            }
+            'V' => {
+                position.y = lexer.number()? + offset.y;
+                current.points.push(position);
+            }
+            'C' | 'S' => {
+                let first = match next.to_ascii_uppercase() {
+                    'C' => lexer.point()? + offset,
+                    _ => match last_control {
+                        Some(('C' | 'S', previous)) => 2. * position - previous,
+                        _ => position,
+                    },
+                };
+                let second = lexer.point()? + offset;
+                let end = lexer.point()? + offset;
+
+                flatten_cubic(&mut current.points, [position, first, second, end], tolerance, 0);
+                control = Some(second);
+                position = end;
+            }
+            'Q' | 'T' => {
+                let middle = match next.to_ascii_uppercase() {
+                    'Q' => lexer.point()? + offset,
+                    _ => match last_control {
+                        Some(('Q' | 'T', previous)) => 2. * position - previous,
+                        _ => position,
+                    },
+                };
+                let end = lexer.point()? + offset;
+
+                // a quadratic is a cubic with both control points 2/3 of the way to the middle
+                let cubic = [
+                    position,
+                    position + (middle - position) * 2. / 3.,
+                    end + (middle - end) * 2. / 3.,
+                    end,
+                ];
+                flatten_cubic(&mut current.points, cubic, tolerance, 0);
+                control = Some(middle);
+                position = end;
+            }
+            'A' => {
+                let radius = lexer.point()?;
+                let rotation = lexer.number()?.to_radians();
+                let (large_arc, sweep) = (lexer.flag()?, lexer.flag()?);
+                let end = lexer.point()? + offset;
+
+                current.points.extend(
+                    endpoint_arc(position, end, radius, rotation, large_arc, sweep, tolerance)
+                        .into_iter()
+                        .skip(1),
+                );
+                position = end;
+            }
+            'Z' => {
+                current.closed = true;
+                position = start;
+                if current.points.len() > 1 {
+                    polylines.push(std::mem::replace(&mut current, Polyline {
+                        points: vec![position],
+                        closed: false,
+                    }));
+                }
+                // Z takes no arguments, so numbers after it can't repeat it
+                command = None;
+            }
+            other => return Err(format!("unsupported path command {other}")),
+        }
+
+        last_control = control.map(|control| (next.to_ascii_uppercase(), control));
+    }
+
+    if current.points.len() > 1 {
+        polylines.push(current);
+    }
+
+    Ok(polylines)
+}
+
+/// Appends the points of a cubic bezier after its start point, subdividing it until the
+/// control points are within `tolerance` of the chord.
+fn flatten_cubic(points: &mut Vec<Vec2>, [p0, p1, p2, p3]: [Vec2; 4], tolerance: f32, depth: u32) {
+    let chord = p3 - p0;
+    let distance = |point: Vec2| match chord.length() {
+        0. => point.distance(p0),
+        length => chord.perp_dot(point - p0).abs() / length,
+    };
+
+    if depth >= 16 || distance(p1).max(distance(p2)) <= tolerance {
+        points.push(p3);
+        return;
+    }
+
+    // de Casteljau split at t = 0.5
+    let (p01, p12, p23) = (p0.midpoint(p1), p1.midpoint(p2), p2.midpoint(p3));
+    let (p012, p123) = (p01.midpoint(p12), p12.midpoint(p23));
+    let middle = p012.midpoint(p123);
+
+    flatten_cubic(points, [p0, p01, p012, middle], tolerance, depth + 1);
+    flatten_cubic(points, [middle, p123, p23, p3], tolerance, depth + 1);
+}
+
+/// Points along an elliptical arc, including both ends
+fn arc_points(
+    center: Vec2,
+    radius: Vec2,
+    rotation: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+    tolerance: f32,
+) -> Vec<Vec2> {
+    // the angle step at which a chord deviates `tolerance` from the circle
+    let max_radius = radius.max_element().max(f32::EPSILON);
+    let step = 2. * (1. - (tolerance / max_radius).min(1.)).acos();
+    let segments = (sweep_angle.abs() / step.max(0.01)).ceil().clamp(1., 1024.) as usize;
+
+    let rotation = Vec2::from_angle(rotation);
+    (0..=segments)
+        .map(|index| {
+            let angle = start_angle + sweep_angle * index as f32 / segments as f32;
+            center + rotation.rotate(Vec2::from_angle(angle) * radius)
+        })
+        .collect()
+}
+
+/// Converts the endpoint parametrization of an SVG arc to its center, and flattens it. See the
+/// "Elliptical arc implementation notes" of the SVG specification.
+fn endpoint_arc(
+    from: Vec2,
+    to: Vec2,
+    radius: Vec2,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    tolerance: f32,
+) -> Vec<Vec2> {
+    let mut radius = radius.abs();
+    if from == to || radius.x == 0. || radius.y == 0. {
+        return vec![from, to];
+    }
+
+    let unrotate = Vec2::from_angle(-rotation);
+    let p = unrotate.rotate((from - to) / 2.);
+
+    // scale up radii that are too small to reach the end point
+    let lambda = (p / radius).length_squared();
+    if lambda > 1. {
+        radius *= lambda.sqrt();
+    }
+
+    let (rx2, ry2) = (radius.x * radius.x, radius.y * radius.y);
+    let numerator = rx2 * ry2 - rx2 * p.y * p.y - ry2 * p.x * p.x;
+    let denominator = rx2 * p.y * p.y + ry2 * p.x * p.x;
+    let mut factor = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+
+    let center_rotated = factor * Vec2::new(radius.x * p.y / radius.y, -radius.y * p.x / radius.x);
+    let center = Vec2::from_angle(rotation).rotate(center_rotated) + (from + to) / 2.;
+
+    let start = (p - center_rotated) / radius;
+    let end = (-p - center_rotated) / radius;
+    let start_angle = start.to_angle();
+    let mut sweep_angle = start.angle_to(end);
+
+    if sweep && sweep_angle < 0. {
+        sweep_angle += TAU;
+    } else if !sweep && sweep_angle > 0. {
+        sweep_angle -= TAU;
+    }
+
+    let mut points = arc_points(center, radius, rotation, start_angle, sweep_angle, tolerance);
+    // avoid gaps from rounding errors
+    *points.last_mut().unwrap() = to;
+    points
+}
+
+/// Loads an SVG drawing as a flat [Model] on the z=0 plane, with its [Mesh] as a labeled
+/// sub-asset like the [ModelLoader](super::model::ModelLoader). Paths, lines, polylines,
+/// polygons, rects, circles and ellipses are supported, and the stroke becomes the line colour.
+/// Fills are ignored, so shapes without a stroke don't show up.
+pub struct SvgLoader;
+impl AssetLoader for SvgLoader {
+    type Asset = Model;
+    type Settings = SvgSettings;
+    type Error = SvgLoaderError;
+
+    fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        settings: &Self::Settings,
+        load_context: &mut bevy::asset::LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        async move {
+            let io_error = |source| SvgLoaderError::Io {
+                path: load_context.path().to_path_buf(),
+                source,
+            };
+
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await.map_err(io_error)?;
+            let source = String::from_utf8(bytes).map_err(|err| {
+                io_error(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+            })?;
+
+            let mut model = parse_svg(&source, settings.tolerance, load_context.path())?;
+            model.apply_settings(&settings.model);
+
+            let mesh: Mesh = LineList::from(model.clone()).into();
+            load_context.add_labeled_asset("mesh".to_string(), mesh);
+
+            Ok(model)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["svg"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints(model: &Model) -> Vec<[Vec2; 2]> {
+        model
+            .lines
+            .iter()
+            .map(|line| [line.start.truncate(), line.end.truncate()])
+            .collect()
+    }
+
+    #[test]
+    fn svg_shapes() {
+        let source = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+                <g stroke="#ff0000" transform="translate(1, 0)">
+                    <path d="M0 0h2v2z" />
+                    <line x1="0" y1="0" x2="0" y2="1" style="stroke: #00ff00" />
+                    <rect width="1" height="1" stroke="none" />
+                </g>
+                <circle cx="5" cy="5" r="1" fill="#ff0000" />
+            </svg>
+        "##;
+
+        let model = parse_svg(source, 0.5, Path::new("shapes.svg")).unwrap();
+
+        assert_eq!(endpoints(&model), vec![
+            [Vec2::new(1., 0.), Vec2::new(3., 0.)],
+            [Vec2::new(3., 0.), Vec2::new(3., -2.)],
+            [Vec2::new(3., -2.), Vec2::new(1., 0.)],
+            [Vec2::new(1., 0.), Vec2::new(1., -1.)],
+        ]);
+        assert_eq!(model.lines[0].color, Some(Srgba::RED.into()));
+        assert_eq!(model.lines[3].color, Some(Srgba::GREEN.into()));
+    }
+
+    #[test]
+    fn curves_are_flattened() {
+        let source =
+            r#"<svg stroke="white"><path d="M0,0 C0,10 10,10 10,0 A5 5 0 0 1 0 0" /></svg>"#;
+
+        let model = parse_svg(source, 0.01, Path::new("curves.svg")).unwrap();
+
+        assert!(model.lines.len() > 20, "{}", model.lines.len());
+        for line in &model.lines {
+            assert!(line.start.y <= 7.51 && line.start.y >= -7.51, "{line:?}");
+        }
+        assert_eq!(model.lines.last().unwrap().end, Vec3::ZERO);
+    }
+
+    #[test]
+    fn subpath_after_empty_close() {
+        let polylines = parse_path("M 0 0 Z M 1 1 L 2 2", 0.5).unwrap();
+
+        assert_eq!(polylines.len(), 1);
+        assert_eq!(polylines[0].points, vec![Vec2::ONE, Vec2::splat(2.)]);
+        assert!(!polylines[0].closed);
+    }
+
+    #[test]
+    fn path_lexer() {
+        let mut lexer = Lexer::new("1-2.5.5e1,+3");
+
+        assert_eq!(lexer.number(), Ok(1.));
+        assert_eq!(lexer.number(), Ok(-2.5));
+        assert_eq!(lexer.number(), Ok(5.));
+        assert_eq!(lexer.number(), Ok(3.));
+        assert!(!lexer.has_number());
+    }
+}
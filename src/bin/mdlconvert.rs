@@ -0,0 +1,349 @@
+//! Converts a Wavefront OBJ or glTF file, e.g. exported from Blender, into a `mdl.json` model.
+//!
+//! Only edges are kept: `l` elements and face outlines from OBJ files, and line primitives from
+//! glTF files. glTF has no polygons, only triangles, so the edges of faces can't be told apart
+//! from the diagonals of their triangulation and triangle primitives are skipped. Export models
+//! with faces as OBJ, or tick "Loose Edges" in the glTF exporter for meshes made of edges only.
+//! Vertex colours become the colours of the vertices.
+//! The game is Z-up, so export with Z up (OBJ: "Up Axis Z", glTF: untick "+Y Up").
+//!
+//! Usage: `cargo run --bin mdlconvert -- <input.obj|input.gltf|input.glb> [output.mdl.json]`
+
+#![feature(array_chunks)]
+#![feature(array_windows)]
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use bevy::{
+    color::{Color, ColorToComponents as _},
+    math::Mat4,
+    prelude::Vec3,
+};
+use gltf::mesh::Mode;
+use lasergame::assets::model::{ModelFile, Vertex};
+
+#[derive(Debug, thiserror::Error)]
+enum ConvertError {
+    #[error("usage: mdlconvert <input.obj|input.gltf|input.glb> [output.mdl.json]")]
+    Usage,
+    #[error("unsupported file type {}, expected .obj, .gltf or .glb", .0.display())]
+    UnsupportedFile(PathBuf),
+    #[error("failed to access {}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("{}:{line}: {message}", path.display())]
+    Obj {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("failed to load {}: {source}", path.display())]
+    Gltf { path: PathBuf, source: gltf::Error },
+    #[error("{} contains no edges", .0.display())]
+    NoEdges(PathBuf),
+}
+
+/// Collects vertices and edges, merging duplicate vertices and edges along the way.
+#[derive(Default)]
+struct ModelBuilder {
+    vertices: Vec<Vertex>,
+    indices: HashMap<([u32; 3], Option<[u32; 4]>), usize>,
+    edges: BTreeSet<[usize; 2]>,
+}
+
+impl ModelBuilder {
+    fn vertex(&mut self, position: Vec3, color: Option<Color>) -> usize {
+        let key = (
+            position.to_array().map(f32::to_bits),
+            color.map(|color| color.to_linear().to_f32_array().map(f32::to_bits)),
+        );
+
+        *self.indices.entry(key).or_insert_with(|| {
+            self.vertices.push(match color {
+                Some(color) => Vertex::Colored { position, color: Some(color) },
+                None => Vertex::Position(position),
+            });
+            self.vertices.len() - 1
+        })
+    }
+
+    fn edge(&mut self, a: usize, b: usize) {
+        if a != b {
+            self.edges.insert([a.min(b), a.max(b)]);
+        }
+    }
+
+    fn polyline(&mut self, indices: &[usize], closed: bool) {
+        for &[a, b] in indices.array_windows::<2>() {
+            self.edge(a, b);
+        }
+
+        if let [first, .., last] = indices[..] {
+            if closed && indices.len() > 2 {
+                self.edge(last, first);
+            }
+        }
+    }
+
+    fn build(self) -> ModelFile {
+        ModelFile {
+            vertices: self.vertices,
+            edges: self.edges.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+fn parse_obj(source: &str, path: &Path) -> Result<ModelFile, ConvertError> {
+    let mut builder = ModelBuilder::default();
+    // positions and colours of the `v` elements, resolved to builder indices on first use
+    let mut positions = vec![];
+    let mut resolved: Vec<Option<usize>> = vec![];
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |message: String| ConvertError::Obj {
+            path: path.to_path_buf(),
+            line: number + 1,
+            message,
+        };
+
+        let mut parts = line.split_whitespace();
+        let Some(element) = parts.next() else {
+            continue;
+        };
+        let values: Vec<_> = parts.collect();
+
+        match element {
+            "v" => {
+                let floats = values
+                    .iter()
+                    .map(|value| value.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| error(format!("invalid vertex: {err}")))?;
+
+                let (position, color) = match floats[..] {
+                    [x, y, z] | [x, y, z, _] => (Vec3::new(x, y, z), None),
+                    // Blender writes vertex colours in sRGB
+                    [x, y, z, r, g, b] => (Vec3::new(x, y, z), Some(Color::srgb(r, g, b))),
+                    _ => {
+                        return Err(error(format!("expected 3 or 6 values, got {}", floats.len())))
+                    }
+                };
+
+                positions.push((position, color));
+                resolved.push(None);
+            }
+            "l" | "f" => {
+                let mut indices = vec![];
+                for value in values {
+                    // only the position index of `v/vt/vn` references is used
+                    let index = value.split('/').next().unwrap_or_default();
+                    let index: isize = index
+                        .parse()
+                        .map_err(|err| error(format!("invalid index {value}: {err}")))?;
+
+                    // indices are 1-based, negative ones count back from the latest vertex
+                    let index = match index {
+                        1.. => index as usize - 1,
+                        ..0 => positions.len().wrapping_add_signed(index),
+                        0 => usize::MAX,
+                    };
+                    let Some(&(position, color)) = positions.get(index) else {
+                        return Err(error(format!("vertex {value} is out of range")));
+                    };
+
+                    let vertex =
+                        *resolved[index].get_or_insert_with(|| builder.vertex(position, color));
+                    indices.push(vertex);
+                }
+
+                builder.polyline(&indices, element == "f");
+            }
+            _ => {}
+        }
+    }
+
+    Ok(builder.build())
+}
+
+fn parse_gltf(path: &Path) -> Result<ModelFile, ConvertError> {
+    let (document, buffers, _) = gltf::import(path)
+        .map_err(|source| ConvertError::Gltf { path: path.to_path_buf(), source })?;
+
+    let mut builder = ModelBuilder::default();
+
+    let Some(scene) = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    else {
+        return Ok(builder.build());
+    };
+
+    let mut nodes: Vec<_> = scene.nodes().map(|node| (node, Mat4::IDENTITY)).collect();
+
+    while let Some((node, parent)) = nodes.pop() {
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        nodes.extend(node.children().map(|child| (child, transform)));
+
+        let Some(mesh) = node.mesh() else {
+            continue;
+        };
+
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let colors: Vec<_> = reader
+                .read_colors(0)
+                .map(|colors| colors.into_rgba_f32().collect())
+                .unwrap_or_default();
+
+            let vertices: Vec<_> = positions
+                .enumerate()
+                .map(|(index, position)| {
+                    let position = transform.transform_point3(position.into());
+                    // glTF vertex colours are linear
+                    let color = colors
+                        .get(index)
+                        .map(|&[r, g, b, a]| Color::linear_rgba(r, g, b, a));
+                    builder.vertex(position, color)
+                })
+                .collect();
+
+            let indices: Vec<_> = match reader.read_indices() {
+                Some(indices) => indices
+                    .into_u32()
+                    .map(|index| vertices[index as usize])
+                    .collect(),
+                None => vertices,
+            };
+
+            match primitive.mode() {
+                Mode::Lines => {
+                    for &[a, b] in indices.array_chunks::<2>() {
+                        builder.edge(a, b);
+                    }
+                }
+                Mode::LineStrip => builder.polyline(&indices, false),
+                Mode::LineLoop => builder.polyline(&indices, true),
+                Mode::Triangles => eprintln!(
+                    "skipping triangles of mesh {:?}, export faces as OBJ to keep their edges",
+                    mesh.name()
+                ),
+                mode => eprintln!("skipping {mode:?} primitive of mesh {:?}", mesh.name()),
+            }
+        }
+    }
+
+    Ok(builder.build())
+}
+
+fn convert(input: &Path, output: &Path) -> Result<usize, ConvertError> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| ConvertError::Io { path, source }
+    };
+
+    let extension = input
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+
+    let model = match extension.as_deref() {
+        Some("obj") => parse_obj(&std::fs::read_to_string(input).map_err(io_error(input))?, input)?,
+        Some("gltf" | "glb") => parse_gltf(input)?,
+        _ => return Err(ConvertError::UnsupportedFile(input.to_path_buf())),
+    };
+
+    if model.edges.is_empty() {
+        return Err(ConvertError::NoEdges(input.to_path_buf()));
+    }
+
+    let json = serde_json::to_string_pretty(&model).expect("model is serializable");
+    std::fs::write(output, json).map_err(io_error(output))?;
+
+    Ok(model.edges.len())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+
+    let result = match &args[..] {
+        [input] => Ok((input, input.with_extension("mdl.json"))),
+        [input, output] => Ok((input, output.clone())),
+        _ => Err(ConvertError::Usage),
+    }
+    .and_then(|(input, output)| {
+        let edges = convert(input, &output)?;
+        println!("wrote {edges} edges to {}", output.display());
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obj_edges() {
+        let source = "
+            o Plane
+            v 0 0 0 1 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vn 0 0 1
+            f 1//1 2//1 3//1 4//1
+            l 1 3
+            l -1 -4
+        ";
+
+        let model = parse_obj(source, Path::new("plane.obj")).unwrap();
+
+        assert_eq!(model.vertices.len(), 4);
+        assert_eq!(model.edges, vec![[0, 1], [0, 2], [0, 3], [1, 2], [2, 3]]);
+        assert!(matches!(model.vertices[0], Vertex::Colored { .. }));
+    }
+
+    #[test]
+    fn obj_coplanar_quads() {
+        // two quads side by side, the shared edge between them is a real edge
+        let source = "
+            v 0 0 0
+            v 1 0 0
+            v 2 0 0
+            v 0 1 0
+            v 1 1 0
+            v 2 1 0
+            f 1 2 5 4
+            f 2 3 6 5
+        ";
+
+        let model = parse_obj(source, Path::new("panels.obj")).unwrap();
+
+        assert_eq!(model.edges.len(), 7);
+        assert!(model.edges.contains(&[1, 4]));
+        // no diagonals
+        assert!(!model.edges.contains(&[0, 4]) && !model.edges.contains(&[1, 3]));
+    }
+
+    #[test]
+    fn obj_index_out_of_range() {
+        let err = parse_obj("v 0 0 0\nl 1 2", Path::new("broken.obj")).unwrap_err();
+
+        assert_eq!(err.to_string(), "broken.obj:2: vertex 2 is out of range");
+    }
+}
@@ -0,0 +1,473 @@
+//! Checks `mdl.json` models for common problems and prints their stats.
+//!
+//! Usage: `cargo run --bin mdltool -- [--fix] [--compact] [--max-radius R] [--max-color C] [paths]`
+//!
+//! Paths can be model files or directories, and default to `assets/models`. Problems are
+//! zero-length lines, duplicate segments, colour components outside `0..=max-color` and
+//! coordinates further than `max-radius` from the origin. `--fix` removes zero-length and
+//! duplicate lines that no frame animates, and rewrites the file if it removed any, keeping the
+//! indexed form of indexed files. `--compact` also converts the others to the indexed form.
+
+#![feature(array_windows)]
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use lasergame::assets::model::{parse_model, FrameFile, Line, Model, ModelFile, Vertex};
+
+const DEFAULT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/models");
+
+struct Options {
+    fix: bool,
+    compact: bool,
+    /// Largest allowed distance of a point from the origin
+    max_radius: f32,
+    /// Largest allowed colour component, in linear space. Bloom makes values above 1 glow.
+    max_color: f32,
+    paths: Vec<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            fix: false,
+            compact: false,
+            max_radius: 4.,
+            max_color: 20.,
+            paths: vec![],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Problem {
+    ZeroLength { line: usize },
+    Duplicate { line: usize, of: usize },
+    Color { line: usize, color: LinearRgba },
+    OutOfRadius { line: usize, distance: f32 },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::ZeroLength { line } => write!(f, "line {line} has zero length"),
+            Problem::Duplicate { line, of } => write!(f, "line {line} duplicates line {of}"),
+            Problem::Color { line, color } => write!(f, "line {line} has colour {color:?}"),
+            Problem::OutOfRadius { line, distance } => {
+                write!(f, "line {line} reaches {distance:.2} from the origin")
+            }
+        }
+    }
+}
+
+/// Endpoints and colours of a line, compared bitwise and regardless of direction
+fn segment_key(line: &Line, default_color: Option<Color>) -> [[u32; 7]; 2] {
+    let colors = line.colors(default_color);
+    let mut ends = [0, 1].map(|end| {
+        let position = [line.start, line.end][end].to_array();
+        let color = colors[end].to_f32_array_no_alpha();
+        [position[0], position[1], position[2], color[0], color[1], color[2], colors[end].alpha]
+            .map(f32::to_bits)
+    });
+    ends.sort();
+    ends
+}
+
+fn check(model: &Model, options: &Options) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut seen = HashMap::new();
+
+    for (index, line) in model.lines.iter().enumerate() {
+        if line.start == line.end {
+            problems.push(Problem::ZeroLength { line: index });
+        }
+
+        if let Some(&of) = seen.get(&segment_key(line, model.default_color)) {
+            problems.push(Problem::Duplicate { line: index, of });
+        } else {
+            seen.insert(segment_key(line, model.default_color), index);
+        }
+
+        let out_of_range = |c: f32| !(0. ..=options.max_color).contains(&c);
+        for color in line.colors(model.default_color) {
+            if color.to_f32_array_no_alpha().into_iter().any(out_of_range) || color.alpha > 1. {
+                problems.push(Problem::Color { line: index, color });
+                break;
+            }
+        }
+
+        let distance = line.start.length().max(line.end.length());
+        if distance > options.max_radius {
+            problems.push(Problem::OutOfRadius { line: index, distance });
+        }
+    }
+
+    problems
+}
+
+/// Removes zero-length and duplicate lines, and the same lines of every frame. Lines that a frame
+/// moves away from the rest pose are kept, since they may not be degenerate there.
+fn dedupe(model: &mut Model) -> usize {
+    let still = |index: usize| {
+        let line = &model.lines[index];
+        model
+            .frames
+            .iter()
+            .all(|frame| frame.lines[index] == [line.start, line.end])
+    };
+
+    let problems = check(model, &Options {
+        max_radius: f32::INFINITY,
+        max_color: f32::INFINITY,
+        ..default()
+    });
+    let mut remove: Vec<_> = problems
+        .iter()
+        .filter_map(|problem| match *problem {
+            Problem::ZeroLength { line } if still(line) => Some(line),
+            Problem::Duplicate { line, of } if still(line) && still(of) => Some(line),
+            _ => None,
+        })
+        .collect();
+    remove.sort();
+    remove.dedup();
+
+    for &index in remove.iter().rev() {
+        model.lines.remove(index);
+        for frame in model.frames.iter_mut() {
+            frame.lines.remove(index);
+        }
+    }
+
+    remove.len()
+}
+
+/// The indexed form, with shared vertices and edges, and the vertex positions of every frame
+fn compact(model: Model) -> ModelFile {
+    let mut vertices = vec![];
+    let mut frame_vertices = vec![vec![]; model.frames.len()];
+    let mut indices = HashMap::new();
+    // vertices are only shared if they also stay together in every frame
+    let mut vertex = |position: Vec3, color: Option<Color>, poses: Vec<Vec3>| {
+        let vertex = match color {
+            Some(color) => Vertex::Colored { position, color: Some(color) },
+            None => Vertex::Position(position),
+        };
+        let key = (
+            serde_json::to_string(&vertex).expect("vertex is serializable"),
+            poses
+                .iter()
+                .map(|pose| pose.to_array().map(f32::to_bits))
+                .collect::<Vec<_>>(),
+        );
+
+        *indices.entry(key).or_insert_with(|| {
+            vertices.push(vertex);
+            for (frame, pose) in frame_vertices.iter_mut().zip(poses) {
+                frame.push(pose);
+            }
+            vertices.len() - 1
+        })
+    };
+
+    let edges = model
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let poses = |end: usize| {
+                model
+                    .frames
+                    .iter()
+                    .map(move |frame| frame.lines[index][end])
+            };
+            [
+                vertex(line.start, line.color, poses(0).collect()),
+                vertex(line.end, line.end_color.or(line.color), poses(1).collect()),
+            ]
+        })
+        .collect();
+
+    let frames = model
+        .frames
+        .iter()
+        .zip(frame_vertices)
+        .map(|(frame, vertices)| FrameFile {
+            name: frame.name.clone(),
+            vertices,
+            ..default()
+        })
+        .collect();
+
+    ModelFile {
+        vertices,
+        edges,
+        frames,
+        sockets: model.sockets,
+        default_color: model.default_color,
+        ..default()
+    }
+}
+
+fn model_paths(paths: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut models = vec![];
+
+    for path in paths {
+        if !path.is_dir() {
+            models.push(path.clone());
+            continue;
+        }
+
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.to_string_lossy().ends_with(".mdl.json") {
+                models.push(path);
+            }
+        }
+    }
+
+    models.sort();
+    Ok(models)
+}
+
+/// Checks, prints and optionally fixes one model. Returns the number of remaining problems.
+fn process(path: &Path, options: &Options) -> Result<usize, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let mut model = parse_model(&bytes, path).map_err(|err| err.to_string())?;
+
+    let (min, max) = model.bounds().unwrap_or_default();
+    println!(
+        "{}: {} lines, {} frames, {} sockets, bounds {min} to {max}",
+        path.display(),
+        model.lines.len(),
+        model.frames.len(),
+        model.sockets.len(),
+    );
+
+    let mut problems = check(&model, options);
+
+    if options.fix {
+        let removed = dedupe(&mut model);
+
+        let indexed =
+            serde_json::from_slice::<ModelFile>(&bytes).is_ok_and(|file| !file.vertices.is_empty());
+
+        if removed > 0 || (options.compact && !indexed) {
+            let file = match options.compact || indexed {
+                true => compact(model.clone()),
+                false => model.clone().into(),
+            };
+            let json = serde_json::to_string_pretty(&file).expect("model is serializable");
+            std::fs::write(path, json + "\n")
+                .map_err(|err| format!("{}: {err}", path.display()))?;
+        }
+
+        println!("  removed {removed} lines");
+        problems = check(&model, options);
+    }
+
+    for problem in problems.iter() {
+        println!("  {problem}");
+    }
+
+    Ok(problems.len())
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| -> Result<f32, String> {
+            let value = args.next().ok_or(format!("{name} needs a value"))?;
+            value
+                .parse()
+                .map_err(|_| format!("{name}: {value} is not a number"))
+        };
+
+        match arg.as_str() {
+            "--fix" => options.fix = true,
+            "--compact" => options.compact = true,
+            "--max-radius" => options.max_radius = value("--max-radius")?,
+            "--max-color" => options.max_color = value("--max-color")?,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+            path => options.paths.push(path.into()),
+        }
+    }
+
+    if options.compact && !options.fix {
+        return Err("--compact only applies with --fix".to_string());
+    }
+
+    if options.paths.is_empty() {
+        options.paths.push(DEFAULT_DIR.into());
+    }
+
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let paths = match model_paths(&options.paths) {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failed = false;
+    for path in paths {
+        match process(&path, &options) {
+            Ok(0) => {}
+            Ok(_) => failed = true,
+            Err(err) => {
+                eprintln!("{err}");
+                failed = true;
+            }
+        }
+    }
+
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lasergame::assets::model::Frame;
+
+    use super::*;
+
+    fn line(start: Vec3, end: Vec3) -> Line {
+        Line {
+            start,
+            end,
+            color: None,
+            end_color: None,
+        }
+    }
+
+    #[test]
+    fn bundled_models_are_clean() {
+        let options = parse_args([]).unwrap();
+
+        let paths = model_paths(&options.paths).unwrap();
+        assert!(!paths.is_empty());
+
+        for path in paths {
+            assert_eq!(process(&path, &options), Ok(0), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn problems_and_fixes() {
+        let mut model = Model {
+            lines: vec![
+                line(Vec3::ZERO, Vec3::X),
+                line(Vec3::X, Vec3::ZERO),
+                line(Vec3::Y, Vec3::Y),
+                line(Vec3::ZERO, Vec3::X * 10.),
+            ],
+            ..default()
+        };
+
+        assert_eq!(check(&model, &Options::default()), vec![
+            Problem::Duplicate { line: 1, of: 0 },
+            Problem::ZeroLength { line: 2 },
+            Problem::OutOfRadius { line: 3, distance: 10. },
+        ]);
+
+        assert_eq!(dedupe(&mut model), 2);
+        assert_eq!(model.lines.len(), 2);
+
+        let file = compact(model);
+        assert_eq!(file.vertices.len(), 3);
+        assert_eq!(file.edges, vec![[0, 1], [0, 2]]);
+
+        // degenerate in the rest pose, but not in a frame
+        let mut animated = Model {
+            lines: vec![line(Vec3::ZERO, Vec3::X), line(Vec3::Y, Vec3::Y)],
+            frames: vec![Frame {
+                name: "open".into(),
+                lines: vec![[Vec3::ZERO, Vec3::X], [Vec3::Y, Vec3::Y * 2.]],
+            }],
+            ..default()
+        };
+        assert_eq!(dedupe(&mut animated), 0);
+        assert_eq!(animated.lines.len(), 2);
+    }
+
+    #[test]
+    fn fix_keeps_source_form() {
+        let dir = std::env::temp_dir().join(format!("mdltool-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let options = Options {
+            fix: true,
+            paths: vec![dir.clone()],
+            ..default()
+        };
+
+        // clean files are left untouched
+        let clean = dir.join("clean.mdl.json");
+        let json = r#"{"vertices":[[0,0,0],[1,0,0]],"edges":[[0,1]]}"#;
+        std::fs::write(&clean, json).unwrap();
+        assert_eq!(process(&clean, &options), Ok(0));
+        assert_eq!(std::fs::read_to_string(&clean).unwrap(), json);
+
+        // indexed files stay indexed
+        let duplicate = dir.join("duplicate.mdl.json");
+        let json = r#"{"vertices":[[0,0,0],[1,0,0]],"edges":[[0,1],[1,0]]}"#;
+        std::fs::write(&duplicate, json).unwrap();
+        assert_eq!(process(&duplicate, &options), Ok(0));
+        let file: ModelFile = serde_json::from_slice(&std::fs::read(&duplicate).unwrap()).unwrap();
+        assert!(file.lines.is_empty());
+        assert_eq!(file.edges, vec![[0, 1]]);
+
+        // clean files in the lines form are compacted on request
+        let lines = dir.join("lines.mdl.json");
+        let json = r#"{"lines":[{"start":[0,0,0],"end":[1,0,0]},{"start":[1,0,0],"end":[1,1,0]}]}"#;
+        std::fs::write(&lines, json).unwrap();
+        let options = Options { compact: true, ..options };
+        assert_eq!(process(&lines, &options), Ok(0));
+        let file: ModelFile = serde_json::from_slice(&std::fs::read(&lines).unwrap()).unwrap();
+        assert!(file.lines.is_empty());
+        assert_eq!(file.vertices.len(), 3);
+        assert_eq!(file.edges, vec![[0, 1], [1, 2]]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn compact_frames() {
+        let model = Model {
+            lines: vec![line(Vec3::ZERO, Vec3::X), line(Vec3::X, Vec3::Y)],
+            frames: vec![Frame {
+                name: "open".into(),
+                // the shared vertex at X splits apart in the frame
+                lines: vec![[Vec3::ZERO, Vec3::X], [Vec3::X * 2., Vec3::Y]],
+            }],
+            ..default()
+        };
+
+        let file = compact(model.clone());
+        assert!(file.lines.is_empty());
+        assert_eq!(file.vertices.len(), 4);
+        assert_eq!(file.frames[0].vertices.len(), 4);
+
+        let roundtrip = Model::try_from(file).unwrap();
+        assert_eq!(roundtrip.frames, model.frames);
+    }
+}
@@ -0,0 +1,408 @@
+//! Optional vector-monitor post-processing pass.
+//!
+//! Runs after tonemapping in two steps: the frame is first merged into a decaying history
+//! texture (phosphor persistence), which is then drawn to the screen with barrel distortion,
+//! chromatic offset and scanlines. Add [CrtSettings] to a camera to use it.
+
+use bevy::{
+    core::FrameCount,
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        camera::ExtractedCamera,
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        render_graph::{
+            NodeRunError, RenderGraphApp as _, RenderGraphContext, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::{CachedTexture, TextureCache},
+        view::{ExtractedView, ViewTarget},
+        Render, RenderApp, RenderSet,
+    },
+};
+
+const SHADER_ASSET_PATH: &str = "shaders/crt.wgsl";
+
+pub struct CrtPlugin;
+impl Plugin for CrtPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CrtSettings>().add_plugins((
+            ExtractComponentPlugin::<CrtSettings>::default(),
+            UniformComponentPlugin::<CrtUniform>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<CrtPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    prepare_pipelines.in_set(RenderSet::Prepare),
+                    prepare_history_textures.in_set(RenderSet::PrepareResources),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<CrtNode>>(Core3d, CrtLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (Node3d::Tonemapping, CrtLabel, Node3d::EndMainPassPostProcessing),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<CrtPipeline>();
+    }
+}
+
+/// Settings for the CRT pass on a camera. All effects can be tuned live from the inspector.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct CrtSettings {
+    pub enabled: bool,
+    /// How much darker the gaps between scanlines are, 0 disables scanlines
+    pub scanline_intensity: f32,
+    /// Number of scanlines over the height of the screen
+    pub scanline_count: f32,
+    /// Fraction of the previous frame's brightness that is kept, 0 disables persistence
+    pub persistence: f32,
+    /// Strength of the barrel distortion, 0 is flat
+    pub distortion: f32,
+    /// Distance between the red and blue channels at the edge of the screen, in uv units
+    pub chromatic_offset: f32,
+}
+
+impl Default for CrtSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scanline_intensity: 0.3,
+            scanline_count: 360.,
+            persistence: 0.8,
+            distortion: 0.05,
+            chromatic_offset: 0.002,
+        }
+    }
+}
+
+#[derive(Component, ShaderType, Clone)]
+pub struct CrtUniform {
+    scanline_intensity: f32,
+    scanline_count: f32,
+    persistence: f32,
+    distortion: f32,
+    chromatic_offset: f32,
+}
+
+impl ExtractComponent for CrtSettings {
+    type QueryData = &'static CrtSettings;
+    type QueryFilter = ();
+    type Out = CrtUniform;
+
+    fn extract_component(settings: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        settings.enabled.then(|| CrtUniform {
+            scanline_intensity: settings.scanline_intensity,
+            scanline_count: settings.scanline_count,
+            persistence: settings.persistence.clamp(0., 1.),
+            distortion: settings.distortion,
+            chromatic_offset: settings.chromatic_offset,
+        })
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct CrtLabel;
+
+#[derive(Default)]
+struct CrtNode;
+
+impl ViewNode for CrtNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static CrtUniform,
+        &'static CrtHistoryTextures,
+        &'static CrtPipelineIds,
+        &'static DynamicUniformIndex<CrtUniform>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _, history, pipeline_ids, uniform_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let crt_pipeline = world.resource::<CrtPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let (Some(persistence_pipeline), Some(display_pipeline)) = (
+            pipeline_cache.get_render_pipeline(pipeline_ids.persistence),
+            pipeline_cache.get_render_pipeline(pipeline_ids.display),
+        ) else {
+            return Ok(());
+        };
+
+        let Some(uniforms) = world.resource::<ComponentUniforms<CrtUniform>>().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+
+        let persistence_bind_group = render_context.render_device().create_bind_group(
+            "crt_persistence_bind_group",
+            &crt_pipeline.persistence_layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &history.read.default_view,
+                &crt_pipeline.sampler,
+                uniforms.clone(),
+            )),
+        );
+
+        let display_bind_group = render_context.render_device().create_bind_group(
+            "crt_display_bind_group",
+            &crt_pipeline.display_layout,
+            &BindGroupEntries::sequential((
+                &history.write.default_view,
+                &crt_pipeline.sampler,
+                uniforms,
+            )),
+        );
+
+        {
+            let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("crt_persistence_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &history.write.default_view,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_render_pipeline(persistence_pipeline);
+            pass.set_bind_group(0, &persistence_bind_group, &[uniform_index.index()]);
+            pass.draw(0..3, 0..1);
+        }
+
+        let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("crt_display_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_render_pipeline(display_pipeline);
+        pass.set_bind_group(0, &display_bind_group, &[uniform_index.index()]);
+        pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct CrtPipeline {
+    persistence_layout: BindGroupLayout,
+    display_layout: BindGroupLayout,
+    sampler: Sampler,
+    shader: Handle<Shader>,
+}
+
+impl FromWorld for CrtPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let persistence_layout = render_device.create_bind_group_layout(
+            "crt_persistence_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<CrtUniform>(true),
+                ),
+            ),
+        );
+
+        let display_layout = render_device.create_bind_group_layout(
+            "crt_display_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<CrtUniform>(true),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        Self {
+            persistence_layout,
+            display_layout,
+            sampler,
+            shader: world.load_asset(SHADER_ASSET_PATH),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+struct CrtPipelineKey {
+    pass: CrtPass,
+    hdr: bool,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+enum CrtPass {
+    Persistence,
+    Display,
+}
+
+impl SpecializedRenderPipeline for CrtPipeline {
+    type Key = CrtPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let (label, layout, entry_point) = match key.pass {
+            CrtPass::Persistence => {
+                ("crt_persistence_pipeline", &self.persistence_layout, "persistence")
+            }
+            CrtPass::Display => ("crt_display_pipeline", &self.display_layout, "display"),
+        };
+
+        RenderPipelineDescriptor {
+            label: Some(label.into()),
+            layout: vec![layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs: vec![],
+                entry_point: entry_point.into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct CrtPipelineIds {
+    persistence: CachedRenderPipelineId,
+    display: CachedRenderPipelineId,
+}
+
+fn prepare_pipelines(
+    views: Query<(Entity, &ExtractedView), With<CrtUniform>>,
+    pipeline: Res<CrtPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<CrtPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    mut commands: Commands,
+) {
+    for (entity, view) in views.iter() {
+        let mut specialize = |pass| {
+            pipelines.specialize(&pipeline_cache, &pipeline, CrtPipelineKey { pass, hdr: view.hdr })
+        };
+
+        commands.entity(entity).insert(CrtPipelineIds {
+            persistence: specialize(CrtPass::Persistence),
+            display: specialize(CrtPass::Display),
+        });
+    }
+}
+
+/// Ping-pong textures holding the accumulated image of previous frames.
+#[derive(Component)]
+struct CrtHistoryTextures {
+    write: CachedTexture,
+    read: CachedTexture,
+}
+
+fn prepare_history_textures(
+    views: Query<(Entity, &ExtractedCamera, &ExtractedView), With<CrtUniform>>,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    frame_count: Res<FrameCount>,
+    mut commands: Commands,
+) {
+    for (entity, camera, view) in views.iter() {
+        let Some(size) = camera.physical_target_size else {
+            continue;
+        };
+
+        let mut descriptor = TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: if view.hdr {
+                ViewTarget::TEXTURE_FORMAT_HDR
+            } else {
+                TextureFormat::bevy_default()
+            },
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        };
+
+        descriptor.label = Some("crt_history_1_texture");
+        let history_1 = texture_cache.get(&render_device, descriptor.clone());
+
+        descriptor.label = Some("crt_history_2_texture");
+        let history_2 = texture_cache.get(&render_device, descriptor);
+
+        let (write, read) = if frame_count.0 % 2 == 0 {
+            (history_1, history_2)
+        } else {
+            (history_2, history_1)
+        };
+
+        commands
+            .entity(entity)
+            .insert(CrtHistoryTextures { write, read });
+    }
+}
@@ -0,0 +1,503 @@
+use avian3d::prelude::*;
+use bevy::{
+    asset::AssetLoader,
+    prelude::*,
+    utils::{ConditionalSendFuture, Duration},
+};
+use rand::Rng as _;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    behaviour::{Aim, Attack, Chase, Flee, Flock, Leash, Patrol, Perception, Think},
+    spawner::SpawnedBy,
+    split::{Generation, Split},
+};
+use crate::{
+    assets::{
+        collider::{ModelCollider, ModelColliderShape},
+        socket::SocketModel,
+    },
+    instanced_lines::InstancedLines,
+    misc::{GameLayer, MovementSpeed, LOCKED_AXES},
+    particles::{shatter::Shatter, DeathEffect, HitEffect},
+    team::Team,
+    weapon::{
+        damage::{Armor, Damage, DamageType, FatalDamage, Health, Hitbox, ImpactDamage},
+        ActiveWeapon, Weapon,
+    },
+};
+
+/// An enemy type, loaded from a `.enemy.ron` file. Paths are relative to the assets folder.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone)]
+pub struct EnemyArchetype {
+    /// Line model, used for the mesh, collider, sockets and shatter fragments
+    pub model: String,
+    pub color: LinearRgba,
+    #[serde(default = "EnemyArchetype::default_collider")]
+    pub collider: ModelColliderShape,
+    pub health: f32,
+    /// Damage dealt to players on contact
+    #[serde(default)]
+    pub impact_damage: Option<f32>,
+    pub movement: MovementSpeed,
+    #[serde(default)]
+    pub behaviours: ArchetypeBehaviours,
+    #[serde(default)]
+    pub weapon: Option<ArchetypeWeapon>,
+    #[serde(default)]
+    pub hit_effect: Option<String>,
+    #[serde(default)]
+    pub death_effect: Option<String>,
+    #[serde(default)]
+    pub drops: Vec<ArchetypeDrop>,
+    #[serde(default)]
+    pub split: Option<Split>,
+    /// Bullets bounce off the main collider, so that only weak points can be hit
+    #[serde(default)]
+    pub armor: bool,
+    /// Extra colliders, e.g. the weak points and armour plates of a boss
+    #[serde(default)]
+    pub parts: Vec<ArchetypePart>,
+    /// Entered in order as health drops, e.g. to attack faster
+    #[serde(default)]
+    pub phases: Vec<ArchetypePhase>,
+}
+
+/// Behaviour components of the enemy, see [behaviour](super::behaviour)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ArchetypeBehaviours {
+    pub patrol: Option<Patrol>,
+    pub perception: Option<Perception>,
+    pub chase: bool,
+    pub attack: Option<Attack>,
+    pub aim: Option<Aim>,
+    pub flee: Option<Flee>,
+    pub flock: Option<Flock>,
+    pub leash: Option<Leash>,
+    /// Seconds between AI ticks, see [Think]
+    pub think_interval: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchetypeWeapon {
+    /// Seconds
+    pub cooldown: f32,
+    #[serde(default)]
+    pub sockets: Vec<String>,
+}
+
+/// A child collider with its own model
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchetypePart {
+    pub model: String,
+    /// The colour of the enemy by default
+    #[serde(default)]
+    pub color: Option<LinearRgba>,
+    pub offset: Vec3,
+    /// Rotation around the z axis, in radians
+    #[serde(default)]
+    pub angle: f32,
+    #[serde(default = "EnemyArchetype::default_collider")]
+    pub collider: ModelColliderShape,
+    pub kind: PartKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PartKind {
+    /// Passes damage on to the enemy, times `multiplier`
+    WeakPoint { multiplier: f32 },
+    /// Deflects bullets
+    Armor,
+}
+
+/// Replaces parts of the archetype once the fraction of health left drops to `health`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchetypePhase {
+    pub health: f32,
+    #[serde(default)]
+    pub behaviours: Option<ArchetypeBehaviours>,
+    #[serde(default)]
+    pub weapon: Option<ArchetypeWeapon>,
+    #[serde(default)]
+    pub movement: Option<MovementSpeed>,
+}
+
+/// Another archetype spawned where the enemy dies
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchetypeDrop {
+    pub archetype: String,
+    /// Probability of dropping, per copy
+    #[serde(default = "ArchetypeDrop::default_chance")]
+    pub chance: f32,
+    #[serde(default = "ArchetypeDrop::default_count")]
+    pub count: u32,
+}
+
+impl EnemyArchetype {
+    fn default_collider() -> ModelColliderShape {
+        ModelColliderShape::ConvexHull
+    }
+}
+
+impl ArchetypeDrop {
+    fn default_chance() -> f32 {
+        1.
+    }
+
+    fn default_count() -> u32 {
+        1
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnemyArchetypeLoaderError {
+    #[error("failed to read enemy archetype: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse enemy archetype: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+pub struct EnemyArchetypeLoader;
+impl AssetLoader for EnemyArchetypeLoader {
+    type Asset = EnemyArchetype;
+    type Settings = ();
+    type Error = EnemyArchetypeLoaderError;
+
+    fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut bevy::asset::LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+
+            Ok(ron::de::from_bytes(&bytes)?)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemy.ron"]
+    }
+}
+
+/// Turns the entity into an enemy of the archetype once it is loaded, and updates it whenever
+/// the archetype file changes. Spawn it with a [Transform].
+#[derive(Component, Clone, Debug)]
+pub struct Archetype(pub Handle<EnemyArchetype>);
+
+/// Number of [phases](EnemyArchetype::phases) the enemy went through
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Phase(pub usize);
+
+/// A collider spawned for one of the [parts](EnemyArchetype::parts) of the enemy
+#[derive(Component)]
+pub struct Part;
+
+pub fn apply_archetypes(
+    mut events: EventReader<AssetEvent<EnemyArchetype>>,
+    enemies: Query<(
+        Entity,
+        Ref<Archetype>,
+        Option<&Health>,
+        Option<&Generation>,
+        Option<&ActiveWeapon>,
+        Option<&Children>,
+    )>,
+    parts: Query<(), With<Part>>,
+    archetypes: Res<Assets<EnemyArchetype>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let changed: Vec<_> = events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, archetype, health, generation, active_weapon, children) in enemies.iter() {
+        if !archetype.is_changed() && !changed.contains(&archetype.0.id()) {
+            continue;
+        }
+
+        let Some(archetype) = archetypes.get(&archetype.0) else {
+            continue;
+        };
+
+        let model = asset_server.load(&archetype.model);
+        let lines = InstancedLines::new(
+            asset_server.load(format!("{}#mesh", archetype.model)),
+            archetype.color,
+        );
+
+        let (health_factor, speed_factor) = match (&archetype.split, generation) {
+            (Some(split), Some(Generation(generation))) => split.factors(*generation),
+            _ => (1., 1.),
+        };
+
+        // keep the fraction of health left when rebalancing live enemies
+        let fraction = health.map_or(1., |health| health.current / health.max);
+        let mut health = Health::max(archetype.health * health_factor);
+        health.current *= fraction;
+
+        let mut movement = archetype.movement;
+        movement.max_speed *= speed_factor;
+
+        let mut enemy = commands.entity(entity);
+        enemy
+            .remove::<(ImpactDamage, HitEffect, DeathEffect, Split, Armor)>()
+            .insert((
+                lines,
+                LOCKED_AXES,
+                Team::Enemy,
+                RigidBody::Dynamic,
+                Restitution::new(0.5),
+                ModelCollider::new(model.clone(), archetype.collider),
+                CollisionLayers::new(GameLayer::Enemy, GameLayer::all_bits()),
+                health,
+                movement,
+                Shatter::new(model.clone(), archetype.color),
+                SocketModel(model),
+            ));
+
+        if let Some(damage) = archetype.impact_damage {
+            enemy.insert(ImpactDamage {
+                damage: Damage {
+                    value: damage,
+                    ty: DamageType::Impact,
+                },
+                despawn_on_impact: false,
+            });
+        }
+        if let Some(effect) = &archetype.hit_effect {
+            enemy.insert(HitEffect(asset_server.load(effect)));
+        }
+        if let Some(effect) = &archetype.death_effect {
+            enemy.insert(DeathEffect(asset_server.load(effect)));
+        }
+
+        if let Some(split) = &archetype.split {
+            enemy.insert(split.clone());
+        }
+        if archetype.armor {
+            enemy.insert(Armor);
+        }
+
+        archetype.behaviours.insert(&mut enemy);
+        set_weapon(&mut commands, entity, archetype.weapon.as_ref(), active_weapon);
+
+        if !archetype.phases.is_empty() {
+            commands.entity(entity).insert(Phase(0));
+        }
+
+        // parts are respawned, since their number can change
+        for child in children.into_iter().flatten() {
+            if parts.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        for part in archetype.parts.iter() {
+            let model = asset_server.load(&part.model);
+            let lines = InstancedLines::new(
+                asset_server.load(format!("{}#mesh", part.model)),
+                part.color.unwrap_or(archetype.color),
+            );
+
+            let mut child = commands.spawn((
+                Part,
+                Transform::from_translation(part.offset)
+                    .with_rotation(Quat::from_rotation_z(part.angle)),
+                lines,
+                ModelCollider::new(model, part.collider),
+                CollisionLayers::new(GameLayer::Enemy, GameLayer::all_bits()),
+                Team::Enemy,
+            ));
+            match part.kind {
+                PartKind::WeakPoint { multiplier } => child.insert(Hitbox { multiplier }),
+                PartKind::Armor => child.insert(Armor),
+            };
+
+            let child = child.id();
+            commands.entity(entity).add_child(child);
+        }
+    }
+}
+
+impl ArchetypeBehaviours {
+    /// Replaces the behaviour components of the enemy
+    fn insert(&self, enemy: &mut EntityCommands) {
+        enemy.remove::<(Patrol, Perception, Chase, Attack, Aim, Flee, Flock, Leash)>();
+        enemy.insert(match self.think_interval {
+            Some(interval) => Think::new(Duration::from_secs_f32(interval)),
+            None => Think::default(),
+        });
+
+        if let Some(patrol) = &self.patrol {
+            enemy.insert(patrol.clone());
+        }
+        if let Some(perception) = &self.perception {
+            enemy.insert(perception.clone());
+        }
+        if self.chase {
+            enemy.insert(Chase);
+        }
+        if let Some(attack) = &self.attack {
+            enemy.insert(attack.clone());
+        }
+        if let Some(aim) = &self.aim {
+            enemy.insert(aim.clone());
+        }
+        if let Some(flee) = &self.flee {
+            enemy.insert(flee.clone());
+        }
+        if let Some(flock) = &self.flock {
+            enemy.insert(flock.clone());
+        }
+        if let Some(leash) = &self.leash {
+            enemy.insert(leash.clone());
+        }
+    }
+}
+
+/// Updates, spawns or despawns the active weapon of the enemy
+fn set_weapon(
+    commands: &mut Commands,
+    entity: Entity,
+    weapon: Option<&ArchetypeWeapon>,
+    active_weapon: Option<&ActiveWeapon>,
+) {
+    match (weapon, active_weapon) {
+        (Some(weapon), active_weapon) => {
+            let weapon = Weapon {
+                cooldown: Duration::from_secs_f32(weapon.cooldown),
+                sockets: weapon.sockets.clone(),
+            };
+
+            match active_weapon {
+                Some(ActiveWeapon(active_weapon)) => {
+                    commands.entity(*active_weapon).insert(weapon);
+                }
+                None => {
+                    let weapon = commands.spawn((weapon, Team::Enemy)).id();
+                    commands
+                        .entity(entity)
+                        .insert(ActiveWeapon(weapon))
+                        .add_child(weapon);
+                }
+            }
+        }
+        (None, Some(ActiveWeapon(active_weapon))) => {
+            commands.entity(*active_weapon).despawn_recursive();
+            commands.entity(entity).remove::<ActiveWeapon>();
+        }
+        (None, None) => {}
+    }
+}
+
+/// Enters the phases of enemies whose health dropped below their thresholds
+pub fn advance_phases(
+    mut enemies: Query<(Entity, &Archetype, &Health, &mut Phase, Option<&ActiveWeapon>)>,
+    archetypes: Res<Assets<EnemyArchetype>>,
+    mut commands: Commands,
+) {
+    for (entity, archetype, health, mut phase, active_weapon) in enemies.iter_mut() {
+        let Some(archetype) = archetypes.get(&archetype.0) else {
+            continue;
+        };
+
+        let fraction = health.current / health.max;
+        let (mut behaviours, mut weapon, mut movement) = (None, None, None);
+        while let Some(next) = archetype.phases.get(phase.0) {
+            if fraction > next.health {
+                break;
+            }
+
+            phase.0 += 1;
+            debug!("{entity} entered phase {}", phase.0);
+
+            // later phases override earlier ones
+            behaviours = next.behaviours.as_ref().or(behaviours);
+            weapon = next.weapon.as_ref().or(weapon);
+            movement = next.movement.or(movement);
+        }
+
+        let mut enemy = commands.entity(entity);
+        if let Some(behaviours) = behaviours {
+            behaviours.insert(&mut enemy);
+        }
+        if let Some(movement) = movement {
+            enemy.insert(movement);
+        }
+        if weapon.is_some() {
+            set_weapon(&mut commands, entity, weapon, active_weapon);
+        }
+    }
+}
+
+/// Spawns the drops of dying enemies
+pub fn drop_archetypes(
+    mut reader: EventReader<FatalDamage>,
+    enemies: Query<(&Archetype, &GlobalTransform, Option<&SpawnedBy>)>,
+    archetypes: Res<Assets<EnemyArchetype>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let mut rng = rand::rng();
+
+    for FatalDamage { target, .. } in reader.read() {
+        let Ok((archetype, transform, spawned_by)) = enemies.get(*target) else {
+            continue;
+        };
+        let Some(archetype) = archetypes.get(&archetype.0) else {
+            continue;
+        };
+
+        for drop in archetype.drops.iter() {
+            for _ in 0..drop.count {
+                if rng.random::<f32>() >= drop.chance {
+                    continue;
+                }
+
+                let mut dropped = commands.spawn((
+                    Archetype(asset_server.load(&drop.archetype)),
+                    Transform::from_translation(transform.translation()),
+                ));
+
+                // waves aren't over until all drops are dead
+                if let Some(spawned_by) = spawned_by {
+                    dropped.insert(*spawned_by);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn parse_bundled_archetypes() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+
+        for name in ["boss", "dot", "gunship", "splitter"] {
+            let path = assets.join(format!("enemies/{name}.enemy.ron"));
+            let source = std::fs::read_to_string(&path).unwrap();
+            let archetype: EnemyArchetype = ron::from_str(&source).unwrap();
+
+            let mut paths = vec![&archetype.model];
+            paths.extend(archetype.hit_effect.iter().chain(&archetype.death_effect));
+            paths.extend(archetype.drops.iter().map(|drop| &drop.archetype));
+            paths.extend(archetype.parts.iter().map(|part| &part.model));
+            for asset in paths {
+                assert!(assets.join(asset).exists(), "{}: {asset} not found", path.display());
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Ai, AiState};
+use crate::weapon::bullet;
+
+/// Turn towards the target while attacking, leading it by its velocity so that bullets hit it.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+pub struct Aim {
+    /// Radians per second
+    pub turn_rate: f32,
+}
+
+/// Where to aim from `shooter` to hit a target at `target` moving at `velocity`, with
+/// projectiles flying at `speed`. The target itself when it can't be caught.
+pub fn lead(shooter: Vec3, target: Vec3, velocity: Vec3, speed: f32) -> Vec3 {
+    // solve |offset + velocity * t| = speed * t for the earliest t > 0
+    let offset = target - shooter;
+    let a = velocity.length_squared() - speed * speed;
+    let b = 2. * offset.dot(velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        (b < 0.).then(|| -c / b)
+    } else {
+        let discriminant = b * b - 4. * a * c;
+        (discriminant >= 0.)
+            .then(|| {
+                let root = discriminant.sqrt();
+                [(-b - root) / (2. * a), (-b + root) / (2. * a)]
+            })
+            .and_then(|times| times.into_iter().filter(|&t| t > 0.).reduce(f32::min))
+    };
+
+    target + velocity * time.unwrap_or(0.)
+}
+
+pub fn aim(
+    mut enemies: Query<(&Ai, &Aim, &mut Transform)>,
+    targets: Query<(&GlobalTransform, Option<&LinearVelocity>)>,
+    time: Res<Time>,
+) {
+    for (ai, aim, mut transform) in enemies.iter_mut() {
+        if ai.state != AiState::Attack {
+            continue;
+        }
+
+        let Some((target, velocity)) = ai.target.and_then(|target| targets.get(target).ok()) else {
+            continue;
+        };
+
+        let velocity = velocity.map_or(Vec3::ZERO, |velocity| velocity.0);
+        let point = lead(transform.translation, target.translation(), velocity, bullet::SPEED);
+        let direction = (point - transform.translation).xy();
+        if direction == Vec2::ZERO {
+            continue;
+        }
+
+        let rotation = Quat::from_rotation_z(direction.to_angle());
+        transform.rotation = transform
+            .rotation
+            .rotate_towards(rotation, aim.turn_rate * time.delta_secs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lead_targeting() {
+        // a target crossing in front meets the bullet where both arrive at the same time
+        let point = lead(Vec3::ZERO, Vec3::new(30., 0., 0.), Vec3::Y * 40., 50.);
+        let time = point.length() / 50.;
+        assert!(point.abs_diff_eq(Vec3::new(30., 40. * time, 0.), 1e-3));
+        assert!((point.y - 40.).abs() < 1e-3);
+
+        // stationary, and too fast to catch
+        assert_eq!(lead(Vec3::ZERO, Vec3::X, Vec3::ZERO, 50.), Vec3::X);
+        assert_eq!(lead(Vec3::ZERO, Vec3::X, Vec3::X * 100., 50.), Vec3::X);
+    }
+}
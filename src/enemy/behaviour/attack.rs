@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{steer, think::Thinking, Ai, AiState};
+use crate::{
+    misc::{MovementSpeed, TargetMovement},
+    weapon::{ActiveWeapon, ShootActiveWeapon},
+};
+
+/// Attack targets within `range`: charge at them, and fire the [ActiveWeapon] if there is one.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+pub struct Attack {
+    pub range: f32,
+    /// Keep the distance instead of ramming the target, for enemies with weapons
+    #[serde(default)]
+    pub hold_position: bool,
+}
+
+pub fn attack(
+    mut enemies: Query<
+        (&Ai, &Attack, &GlobalTransform, &MovementSpeed, &mut TargetMovement),
+        With<Thinking>,
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (ai, attack, transform, speed, mut target_movement) in enemies.iter_mut() {
+        if ai.state != AiState::Attack {
+            continue;
+        }
+
+        let Some(target) = ai.target.and_then(|target| targets.get(target).ok()) else {
+            continue;
+        };
+
+        target_movement.0 = match attack.hold_position {
+            true => Vec2::ZERO,
+            false => steer(transform.translation(), target.translation(), speed.max_speed),
+        };
+    }
+}
+
+/// Fires the [ActiveWeapon] while attacking. This runs every frame rather than on AI ticks, so
+/// the fire rate is up to the weapon's cooldown.
+pub fn fire(
+    enemies: Query<(Entity, &Ai), (With<Attack>, With<ActiveWeapon>)>,
+    targets: Query<(), With<GlobalTransform>>,
+    mut shoot: EventWriter<ShootActiveWeapon>,
+) {
+    for (entity, ai) in enemies.iter() {
+        if ai.state == AiState::Attack && ai.target.is_some_and(|target| targets.contains(target)) {
+            shoot.send(ShootActiveWeapon(entity));
+        }
+    }
+}
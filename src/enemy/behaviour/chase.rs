@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use super::{perception::Perception, steer, think::Thinking, Ai, AiState, ARRIVAL_DISTANCE};
+use crate::misc::{MovementSpeed, TargetMovement};
+
+/// Follow targets picked up by [Perception], and investigate where they were last noticed after
+/// losing them.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+#[require(Ai, Perception)]
+pub struct Chase;
+
+pub fn chase(
+    mut enemies: Query<
+        (&Ai, &GlobalTransform, &MovementSpeed, &mut TargetMovement),
+        (With<Chase>, With<Thinking>),
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (ai, transform, speed, mut target_movement) in enemies.iter_mut() {
+        if ai.state != AiState::Chase {
+            continue;
+        }
+
+        let Some(target) = ai.target.and_then(|target| targets.get(target).ok()) else {
+            continue;
+        };
+
+        target_movement.0 = steer(transform.translation(), target.translation(), speed.max_speed);
+    }
+}
+
+pub fn investigate(
+    mut enemies: Query<
+        (&Ai, &mut Perception, &GlobalTransform, &MovementSpeed, &mut TargetMovement),
+        (With<Chase>, With<Thinking>),
+    >,
+) {
+    for (ai, mut perception, transform, speed, mut target_movement) in enemies.iter_mut() {
+        if ai.state != AiState::Investigate {
+            continue;
+        }
+
+        let Some(last_known) = perception.last_known else {
+            continue;
+        };
+
+        let position = transform.translation();
+        if position.distance(last_known.position) <= ARRIVAL_DISTANCE {
+            debug!("Enemy found nothing");
+            perception.last_known = None;
+            target_movement.0 = Vec2::ZERO;
+        } else {
+            target_movement.0 = steer(position, last_known.position, speed.max_speed);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{steer, think::Thinking, Ai, AiState};
+use crate::misc::{MovementSpeed, TargetMovement};
+
+/// Run away from the target once the fraction of [Health](crate::weapon::damage::Health) left
+/// drops below `health`.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+pub struct Flee {
+    pub health: f32,
+}
+
+pub fn flee(
+    mut enemies: Query<
+        (&Ai, &GlobalTransform, &MovementSpeed, &mut TargetMovement),
+        (With<Flee>, With<Thinking>),
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (ai, transform, speed, mut target_movement) in enemies.iter_mut() {
+        if ai.state != AiState::Flee {
+            continue;
+        }
+
+        let Some(target) = ai.target.and_then(|target| targets.get(target).ok()) else {
+            continue;
+        };
+
+        target_movement.0 = -steer(transform.translation(), target.translation(), speed.max_speed);
+    }
+}
@@ -0,0 +1,157 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{think::Thinking, Ai};
+use crate::{
+    misc::{MovementSpeed, TargetMovement},
+    utils::spatial_hash::SpatialHash,
+};
+
+/// Move as a swarm with other flocking enemies within `radius`, like boids: keep apart from
+/// them, match their velocity and stay together. This is blended with the movement of the
+/// current state, e.g. chasing a target, according to the weights.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+#[serde(default)]
+pub struct Flock {
+    pub radius: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    /// Weight of the movement of the current state
+    pub seek: f32,
+}
+
+impl Default for Flock {
+    fn default() -> Self {
+        Self {
+            radius: 8.,
+            separation: 1.5,
+            alignment: 0.5,
+            cohesion: 0.3,
+            seek: 1.,
+        }
+    }
+}
+
+impl Flock {
+    /// Desired velocity as a fraction of the max speed, given the own and neighbouring
+    /// positions and velocities, and the desired velocity of the current state
+    pub fn steer<'a>(
+        &self,
+        position: Vec3,
+        velocity: Vec2,
+        seek: Vec2,
+        neighbours: impl IntoIterator<Item = &'a (Vec3, Vec2)>,
+    ) -> Vec2 {
+        let (mut separation, mut center, mut average_velocity, mut count) =
+            (Vec2::ZERO, Vec2::ZERO, Vec2::ZERO, 0);
+
+        for &(other, other_velocity) in neighbours {
+            let offset = (position - other).xy();
+            let distance = offset.length();
+            if distance > self.radius {
+                continue;
+            }
+
+            // stronger the closer they are, and pushing apart in some direction when overlapping
+            separation += offset.normalize_or(Vec2::X) * (1. - distance / self.radius);
+            center += other.xy();
+            average_velocity += other_velocity;
+            count += 1;
+        }
+
+        let mut steering = seek * self.seek + separation * self.separation;
+        if count > 0 {
+            let count = count as f32;
+            steering += (average_velocity / count - velocity) * self.alignment;
+            steering += (center / count - position.xy()) / self.radius * self.cohesion;
+        }
+
+        steering
+    }
+}
+
+pub fn flock(
+    mut enemies: Query<(
+        Entity,
+        &Flock,
+        &GlobalTransform,
+        &LinearVelocity,
+        &MovementSpeed,
+        &mut TargetMovement,
+        Has<Thinking>,
+    )>,
+    mut hash: Local<SpatialHash<(Entity, Vec2)>>,
+) {
+    let radius = enemies
+        .iter()
+        .map(|(_, flock, ..)| flock.radius)
+        .fold(1., f32::max);
+    hash.reset(radius);
+
+    for (entity, _, transform, velocity, speed, ..) in enemies.iter() {
+        let velocity = velocity.xy() / speed.max_speed.max(f32::EPSILON);
+        hash.insert(transform.translation(), (entity, velocity));
+    }
+
+    // every flocking enemy is a neighbour, but only the thinking ones steer
+    for (entity, flock, transform, velocity, speed, mut target_movement, thinking) in
+        enemies.iter_mut()
+    {
+        if !thinking {
+            continue;
+        }
+
+        let max_speed = speed.max_speed.max(f32::EPSILON);
+        let position = transform.translation();
+
+        let neighbours: Vec<_> = hash
+            .within(position, flock.radius)
+            .filter(|(_, (other, _))| *other != entity)
+            .map(|&(other, (_, velocity))| (other, velocity))
+            .collect();
+
+        let steering = flock.steer(
+            position,
+            velocity.xy() / max_speed,
+            target_movement.0 / max_speed,
+            &neighbours,
+        );
+        target_movement.0 = steering.clamp_length_max(1.) * max_speed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flocking_forces() {
+        let flock = Flock { alignment: 0., ..default() };
+
+        // pushed away from a close neighbour
+        let steering = flock.steer(Vec3::ZERO, Vec2::ZERO, Vec2::ZERO, &[(Vec3::X, Vec2::ZERO)]);
+        assert!(steering.x < 0.);
+
+        // pulled towards a group at the edge of the radius, where separation fades out
+        let group = [Vec3::new(7.5, 1., 0.), Vec3::new(7.5, -1., 0.)].map(|p| (p, Vec2::ZERO));
+        assert!(flock.steer(Vec3::ZERO, Vec2::ZERO, Vec2::ZERO, &group).x > 0.);
+
+        // nobody around, only seeking
+        let seek = Vec2::new(0., 1.);
+        assert_eq!(flock.steer(Vec3::ZERO, Vec2::ZERO, seek, &[]), seek);
+
+        // matching the velocity of neighbours
+        let flock = Flock {
+            alignment: 1.,
+            separation: 0.,
+            cohesion: 0.,
+            ..default()
+        };
+        let steering = flock.steer(Vec3::ZERO, Vec2::ZERO, Vec2::ZERO, &[(Vec3::Y * 3., Vec2::X)]);
+        assert_eq!(steering, Vec2::X);
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{steer, think::Thinking, Ai, AiState};
+use crate::misc::{MovementSpeed, TargetMovement};
+
+/// Give up and return home when further than `distance` away from it.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+pub struct Leash {
+    pub distance: f32,
+}
+
+pub fn return_home(
+    mut enemies: Query<
+        (&Ai, &GlobalTransform, &MovementSpeed, &mut TargetMovement),
+        (With<Leash>, With<Thinking>),
+    >,
+) {
+    for (ai, transform, speed, mut target_movement) in enemies.iter_mut() {
+        if ai.state == AiState::Return {
+            target_movement.0 = steer(transform.translation(), ai.home, speed.max_speed);
+        }
+    }
+}
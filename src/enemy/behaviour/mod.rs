@@ -0,0 +1,253 @@
+//! Enemy AI as a state machine. Every enemy with an [Ai] is in one [AiState] at a time, and
+//! the behaviour components it carries decide which states it can reach and what it does in
+//! them:
+//!
+//! - [Patrol]: wander around home instead of idling
+//! - [Chase]: follow targets noticed by [Perception], and investigate where they were lost
+//! - [Attack]: charge and fire the active weapon at targets in range
+//! - [Aim]: turn towards the target while attacking, leading it
+//! - [Flee]: run from the target when health is low
+//! - [Flock]: move as a swarm with other flocking enemies
+//! - [Leash]: return home when lured too far away
+//!
+//! New enemy types combine and tune these components instead of adding their own systems.
+//!
+//! Enemies don't think every frame, but at the rate of their [Think], within the
+//! [ThinkBudget](think::ThinkBudget) of the frame.
+
+use bevy::{
+    ecs::{component::ComponentId, query::QueryData, world::DeferredWorld},
+    prelude::*,
+};
+
+use self::think::Thinking;
+pub use self::{
+    aim::Aim, attack::Attack, chase::Chase, flee::Flee, flock::Flock, leash::Leash, patrol::Patrol,
+    perception::Perception, think::Think,
+};
+use crate::{misc::TargetMovement, weapon::damage::Health};
+
+pub mod aim;
+pub mod attack;
+pub mod chase;
+pub mod flee;
+pub mod flock;
+pub mod leash;
+pub mod patrol;
+pub mod perception;
+pub mod think;
+
+/// Distance at which a point counts as reached
+pub const ARRIVAL_DISTANCE: f32 = 2.;
+
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AiState {
+    #[default]
+    Idle,
+    Patrol,
+    Chase,
+    Investigate,
+    Attack,
+    Flee,
+    Return,
+}
+
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+#[component(on_add = set_home)]
+#[require(TargetMovement, Think)]
+pub struct Ai {
+    pub state: AiState,
+    pub target: Option<Entity>,
+    /// Where the enemy was spawned, used by [Patrol] and [Leash]
+    pub home: Vec3,
+}
+
+fn set_home(mut world: DeferredWorld, entity: Entity, _id: ComponentId) {
+    let Some(home) = world.get::<Transform>(entity).map(|t| t.translation) else {
+        return;
+    };
+
+    if let Some(mut ai) = world.get_mut::<Ai>(entity) {
+        ai.home = home;
+    }
+}
+
+#[derive(QueryData)]
+pub struct Behaviours {
+    pub patrol: Option<&'static Patrol>,
+    pub chase: Option<&'static Chase>,
+    pub attack: Option<&'static Attack>,
+    pub flee: Option<&'static Flee>,
+    pub leash: Option<&'static Leash>,
+}
+
+/// What an enemy knows when deciding on its next state
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Situation {
+    pub target_distance: Option<f32>,
+    /// Distance to where the target was last noticed, see [Perception]
+    pub last_known_distance: Option<f32>,
+    pub home_distance: f32,
+    /// Fraction of health left, 1 without [Health]
+    pub health: f32,
+}
+
+pub fn next_state(current: AiState, situation: Situation, behaviours: &BehavioursItem) -> AiState {
+    // returning enemies ignore everything until they are home
+    if current == AiState::Return && situation.home_distance > ARRIVAL_DISTANCE {
+        return AiState::Return;
+    }
+
+    if let Some(leash) = behaviours.leash {
+        if situation.home_distance > leash.distance {
+            return AiState::Return;
+        }
+    }
+
+    if let Some(distance) = situation.target_distance {
+        if behaviours
+            .flee
+            .is_some_and(|flee| situation.health < flee.health)
+        {
+            return AiState::Flee;
+        }
+
+        if behaviours
+            .attack
+            .is_some_and(|attack| distance <= attack.range)
+        {
+            return AiState::Attack;
+        }
+
+        if behaviours.chase.is_some() {
+            return AiState::Chase;
+        }
+    }
+
+    if situation.last_known_distance.is_some() && behaviours.chase.is_some() {
+        return AiState::Investigate;
+    }
+
+    match behaviours.patrol {
+        Some(_) => AiState::Patrol,
+        None => AiState::Idle,
+    }
+}
+
+pub fn decide(
+    mut enemies: Query<
+        (Entity, &mut Ai, &GlobalTransform, Option<&mut Perception>, Option<&Health>, Behaviours),
+        With<Thinking>,
+    >,
+    targets: Query<&GlobalTransform>,
+) {
+    for (entity, mut ai, transform, mut perception, health, behaviours) in enemies.iter_mut() {
+        let position = transform.translation();
+
+        let target_distance = ai
+            .target
+            .and_then(|target| targets.get(target).ok())
+            .map(|target| target.translation().distance(position));
+
+        let situation = Situation {
+            target_distance,
+            last_known_distance: perception
+                .as_ref()
+                .and_then(|perception| perception.last_known)
+                .map(|last_known| last_known.position.distance(position)),
+            home_distance: ai.home.distance(position),
+            health: health.map_or(1., |health| health.current / health.max),
+        };
+
+        let state = next_state(ai.state, situation, &behaviours);
+        if state != ai.state {
+            debug!("{entity}: {:?} -> {state:?}", ai.state);
+
+            if state == AiState::Return {
+                ai.target = None;
+                if let Some(perception) = perception.as_mut() {
+                    perception.last_known = None;
+                }
+            }
+            ai.state = state;
+        }
+    }
+}
+
+/// Desired velocity to move from `position` towards `target` at `speed`
+pub fn steer(position: Vec3, target: Vec3, speed: f32) -> Vec2 {
+    (target - position).xy().normalize_or_zero() * speed
+}
+
+pub fn idle(mut enemies: Query<(&Ai, &mut TargetMovement), With<Thinking>>) {
+    for (ai, mut target_movement) in enemies.iter_mut() {
+        if ai.state == AiState::Idle {
+            target_movement.0 = Vec2::ZERO;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_transitions() {
+        let (patrol, chase) = (
+            Patrol {
+                radius: 10.,
+                speed: 0.3,
+                waypoint: None,
+            },
+            Chase,
+        );
+        let (attack, flee, leash) =
+            (Attack { range: 10., hold_position: false }, Flee { health: 0.3 }, Leash {
+                distance: 100.,
+            });
+        let behaviours = BehavioursItem {
+            patrol: Some(&patrol),
+            chase: Some(&chase),
+            attack: Some(&attack),
+            flee: Some(&flee),
+            leash: Some(&leash),
+        };
+
+        let at = |target_distance, home_distance, health| Situation {
+            target_distance,
+            last_known_distance: None,
+            home_distance,
+            health,
+        };
+
+        assert_eq!(next_state(AiState::Idle, at(None, 0., 1.), &behaviours), AiState::Patrol);
+        assert_eq!(next_state(AiState::Patrol, at(Some(30.), 0., 1.), &behaviours), AiState::Chase);
+        assert_eq!(next_state(AiState::Chase, at(Some(5.), 0., 1.), &behaviours), AiState::Attack);
+        assert_eq!(next_state(AiState::Attack, at(Some(5.), 0., 0.2), &behaviours), AiState::Flee);
+        assert_eq!(
+            next_state(AiState::Chase, at(Some(5.), 150., 1.), &behaviours),
+            AiState::Return
+        );
+        assert_eq!(
+            next_state(AiState::Return, at(Some(5.), 50., 1.), &behaviours),
+            AiState::Return
+        );
+        assert_eq!(next_state(AiState::Return, at(None, 1., 1.), &behaviours), AiState::Patrol);
+
+        let lost = Situation {
+            last_known_distance: Some(20.),
+            ..at(None, 10., 1.)
+        };
+        assert_eq!(next_state(AiState::Chase, lost, &behaviours), AiState::Investigate);
+
+        let idle = BehavioursItem {
+            patrol: None,
+            chase: None,
+            attack: None,
+            flee: None,
+            leash: None,
+        };
+        assert_eq!(next_state(AiState::Idle, at(Some(5.), 0., 1.), &idle), AiState::Idle);
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use rand::Rng as _;
+use serde::{Deserialize, Serialize};
+
+use super::{steer, think::Thinking, Ai, AiState, ARRIVAL_DISTANCE};
+use crate::misc::{MovementSpeed, TargetMovement};
+
+/// Wander between random points around home while there is nothing else to do.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+pub struct Patrol {
+    pub radius: f32,
+    /// Fraction of the max speed to patrol at
+    #[serde(default = "Patrol::default_speed")]
+    pub speed: f32,
+    #[serde(skip)]
+    pub waypoint: Option<Vec3>,
+}
+
+impl Patrol {
+    fn default_speed() -> f32 {
+        0.3
+    }
+}
+
+pub fn patrol(
+    mut enemies: Query<
+        (&Ai, &mut Patrol, &GlobalTransform, &MovementSpeed, &mut TargetMovement),
+        With<Thinking>,
+    >,
+) {
+    let mut rng = rand::rng();
+
+    for (ai, mut patrol, transform, speed, mut target_movement) in enemies.iter_mut() {
+        if ai.state != AiState::Patrol {
+            patrol.waypoint = None;
+            continue;
+        }
+
+        let position = transform.translation();
+        let waypoint = match patrol.waypoint {
+            Some(waypoint) if waypoint.distance(position) > ARRIVAL_DISTANCE => waypoint,
+            _ => {
+                let offset = Vec2::from_angle(rng.random_range(0. ..std::f32::consts::TAU))
+                    * rng.random_range(0. ..=patrol.radius);
+                ai.home + offset.extend(0.)
+            }
+        };
+
+        patrol.waypoint = Some(waypoint);
+        target_movement.0 = steer(position, waypoint, speed.max_speed * patrol.speed);
+    }
+}
@@ -0,0 +1,223 @@
+use std::f32::consts::PI;
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, utils::Duration};
+use serde::{Deserialize, Serialize};
+
+use super::{think::Thinking, Ai, AiState};
+use crate::{misc::GameLayer, player::PlayerShip, team::Team, weapon::Shoot};
+
+/// How an enemy notices players: it sees them within `sight_range` inside its vision cone, unless
+/// map geometry is in the way, and hears them shoot within `hearing_range`. After losing its
+/// target, it remembers where it last noticed it for `memory`.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+#[require(Ai)]
+#[serde(default)]
+pub struct Perception {
+    pub sight_range: f32,
+    /// Half angle of the vision cone around the forward (+X) direction, in radians. [PI] sees all
+    /// around.
+    pub fov: f32,
+    pub hearing_range: f32,
+    pub memory: Duration,
+    pub priority: TargetPriority,
+    #[serde(skip)]
+    pub last_known: Option<LastKnown>,
+}
+
+/// How [Perception] picks a target among the visible players. The current target is kept while
+/// it stays visible.
+#[derive(Reflect, Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetPriority {
+    #[default]
+    Nearest,
+    /// Prefer players that are close and aiming at the enemy
+    Threat,
+}
+
+impl TargetPriority {
+    /// How good a target the player is for the enemy, higher is better. `forward` is the
+    /// player's forward (+X) direction.
+    pub fn score(self, enemy: Vec3, player: Vec3, forward: Vec3) -> f32 {
+        let offset = (enemy - player).xy();
+        let distance = offset.length().max(f32::EPSILON);
+
+        match self {
+            TargetPriority::Nearest => -distance,
+            TargetPriority::Threat => (1. + forward.xy().dot(offset / distance)) / distance,
+        }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct LastKnown {
+    pub position: Vec3,
+    /// Elapsed [Time] when the position was noticed
+    pub time: Duration,
+}
+
+impl Perception {
+    pub fn new(sight_range: f32) -> Self {
+        Self {
+            sight_range,
+            fov: PI,
+            hearing_range: sight_range * 2.,
+            memory: Duration::from_secs(5),
+            priority: default(),
+            last_known: None,
+        }
+    }
+
+    fn remember(&mut self, position: Vec3, time: Duration) {
+        self.last_known = Some(LastKnown { position, time });
+    }
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Self::new(50.)
+    }
+}
+
+/// Whether `offset` lies within `fov` radians of `forward`, in the XY plane
+pub fn in_view(forward: Vec3, offset: Vec3, fov: f32) -> bool {
+    fov >= PI || forward.xy().angle_to(offset.xy()).abs() <= fov
+}
+
+/// Targets visible players according to [TargetPriority]
+pub fn see(
+    mut enemies: Query<(&mut Ai, &mut Perception, &GlobalTransform), With<Thinking>>,
+    players: Query<(Entity, &GlobalTransform), With<PlayerShip>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    let walls = SpatialQueryFilter::from_mask(GameLayer::MapGeometry);
+
+    for (mut ai, mut perception, transform) in enemies.iter_mut() {
+        if perception
+            .last_known
+            .is_some_and(|last_known| time.elapsed() - last_known.time > perception.memory)
+        {
+            perception.last_known = None;
+        }
+
+        if ai.target.is_some_and(|target| !players.contains(target)) {
+            debug!("Enemy target despawned");
+            ai.target = None;
+            perception.last_known = None;
+        }
+
+        if ai.state == AiState::Return {
+            continue;
+        }
+
+        let position = transform.translation();
+        let can_see = |player: Vec3| {
+            let offset = player - position;
+            let distance = offset.length();
+            let Ok(direction) = Dir3::new(offset) else {
+                return true;
+            };
+
+            distance <= perception.sight_range
+                && in_view(transform.right().into(), offset, perception.fov)
+                && spatial_query
+                    .cast_ray(position, direction, distance, true, &walls)
+                    .is_none()
+        };
+
+        let visible: Vec<_> = players
+            .iter()
+            .filter(|(_, player)| can_see(player.translation()))
+            .collect();
+        let seen = match visible
+            .iter()
+            .find(|&&(player, _)| Some(player) == ai.target)
+        {
+            Some(&target) => Some(target),
+            None => visible.into_iter().max_by(|(_, a), (_, b)| {
+                let score = |player: &GlobalTransform| {
+                    perception
+                        .priority
+                        .score(position, player.translation(), player.right().into())
+                };
+                score(a).total_cmp(&score(b))
+            }),
+        };
+
+        match seen {
+            Some((player, player_transform)) => {
+                if ai.target != Some(player) {
+                    debug!("Enemy spotted {player}");
+                    ai.target = Some(player);
+                }
+                perception.remember(player_transform.translation(), time.elapsed());
+            }
+            None => {
+                if let Some(target) = ai.target.take() {
+                    debug!("Enemy lost sight of {target}");
+                }
+            }
+        }
+    }
+}
+
+/// Makes enemies without a target investigate shots of players they hear
+pub fn hear(
+    mut events: EventReader<Shoot>,
+    weapons: Query<(&GlobalTransform, &Team)>,
+    mut enemies: Query<(&Ai, &mut Perception, &GlobalTransform)>,
+    time: Res<Time>,
+) {
+    for Shoot(weapon) in events.read() {
+        let Ok((weapon, Team::Player)) = weapons.get(*weapon) else {
+            continue;
+        };
+        let shot = weapon.translation();
+
+        for (ai, mut perception, transform) in enemies.iter_mut() {
+            if ai.target.is_none()
+                && ai.state != AiState::Return
+                && transform.translation().distance(shot) <= perception.hearing_range
+            {
+                perception.remember(shot, time.elapsed());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    #[test]
+    fn vision_cone() {
+        assert!(in_view(Vec3::X, Vec3::new(10., 5., 0.), FRAC_PI_4));
+        assert!(!in_view(Vec3::X, Vec3::new(5., 10., 0.), FRAC_PI_4));
+        assert!(!in_view(Vec3::X, Vec3::NEG_X, FRAC_PI_4));
+        assert!(in_view(Vec3::X, Vec3::NEG_X, PI));
+    }
+
+    #[test]
+    fn target_priority() {
+        let enemy = Vec3::ZERO;
+        let near = (Vec3::new(0., 10., 0.), Vec3::Y);
+        let aiming = (Vec3::new(-20., 0., 0.), Vec3::X);
+
+        let best = |priority: TargetPriority| {
+            let [near, aiming] =
+                [near, aiming].map(|(player, forward)| priority.score(enemy, player, forward));
+            if near > aiming {
+                "near"
+            } else {
+                "aiming"
+            }
+        };
+
+        assert_eq!(best(TargetPriority::Nearest), "near");
+        assert_eq!(best(TargetPriority::Threat), "aiming");
+    }
+}
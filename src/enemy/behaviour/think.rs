@@ -0,0 +1,131 @@
+use bevy::{
+    diagnostic::{DiagnosticPath, Diagnostics},
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    utils::Duration,
+};
+use rand::Rng as _;
+
+/// Number of enemies that thought this frame
+pub const AI_TICKS: DiagnosticPath = DiagnosticPath::const_new("enemy/ai_ticks");
+
+/// Marks the enemies that think this frame, set by [schedule] only. The state systems only update
+/// these, the rest keep doing what they decided on their last tick.
+#[derive(Component, Debug)]
+pub struct Thinking;
+
+/// How often an enemy runs its AI. Ticks are staggered randomly across the interval, so enemies
+/// spawned together don't all think on the same frame.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+#[component(on_insert = stagger)]
+pub struct Think {
+    pub interval: Duration,
+    /// Elapsed [Time] of the next tick
+    pub next: Duration,
+}
+
+impl Think {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, next: Duration::ZERO }
+    }
+}
+
+impl Default for Think {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(200))
+    }
+}
+
+fn stagger(mut world: DeferredWorld, entity: Entity, _id: ComponentId) {
+    let now = world.resource::<Time>().elapsed();
+
+    if let Some(mut think) = world.get_mut::<Think>(entity) {
+        think.next = now + think.interval.mul_f32(rand::rng().random());
+    }
+}
+
+/// Upper limit of AI ticks per frame. Enemies that are due when it is reached think on the next
+/// frames, most overdue first.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct ThinkBudget {
+    pub max_ticks: usize,
+}
+
+impl Default for ThinkBudget {
+    fn default() -> Self {
+        Self { max_ticks: 64 }
+    }
+}
+
+/// The `budget` most overdue of the `thinkers` that are due at `now`
+pub fn due(
+    thinkers: impl IntoIterator<Item = (Entity, Duration)>,
+    now: Duration,
+    budget: usize,
+) -> Vec<Entity> {
+    let mut due: Vec<_> = thinkers
+        .into_iter()
+        .filter(|&(_, next)| next <= now)
+        .collect();
+
+    if due.len() > budget {
+        due.select_nth_unstable_by_key(budget, |&(_, next)| next);
+        due.truncate(budget);
+    }
+
+    due.into_iter().map(|(entity, _)| entity).collect()
+}
+
+/// Picks the enemies that think this frame, see [Thinking]
+pub fn schedule(
+    mut thinkers: Query<(Entity, &mut Think)>,
+    thinking: Query<Entity, With<Thinking>>,
+    budget: Res<ThinkBudget>,
+    time: Res<Time>,
+    mut diagnostics: Diagnostics,
+    mut commands: Commands,
+) {
+    for entity in thinking.iter() {
+        commands.entity(entity).remove::<Thinking>();
+    }
+
+    let now = time.elapsed();
+    let due =
+        due(thinkers.iter().map(|(entity, think)| (entity, think.next)), now, budget.max_ticks);
+
+    for &entity in &due {
+        if let Ok((_, mut think)) = thinkers.get_mut(entity) {
+            think.next = now + think.interval;
+            commands.entity(entity).try_insert(Thinking);
+        }
+    }
+
+    diagnostics.add_measurement(&AI_TICKS, || due.len() as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budgeted_ticks() {
+        let ms = Duration::from_millis;
+        let thinkers = [
+            (Entity::from_raw(0), ms(100)),
+            (Entity::from_raw(1), ms(50)),
+            (Entity::from_raw(2), ms(300)),
+            (Entity::from_raw(3), ms(0)),
+        ];
+
+        assert_eq!(due(thinkers, ms(200), 10), [0, 1, 3].map(Entity::from_raw));
+
+        // the most overdue go first
+        let mut first = due(thinkers, ms(200), 2);
+        first.sort();
+        assert_eq!(first, [1, 3].map(Entity::from_raw));
+
+        assert!(due(thinkers, ms(200), 0).is_empty());
+    }
+}
@@ -1,23 +1,81 @@
-use std::time::Duration;
+use bevy::{
+    diagnostic::{Diagnostic, RegisterDiagnostic as _},
+    prelude::*,
+};
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
+use self::{
+    archetype::{EnemyArchetype, EnemyArchetypeLoader},
+    wave::{WavePlan, WavePlanLoader},
+};
+use crate::weapon::damage;
 
-pub mod behaviour {
-    pub mod attract;
-}
-pub mod dot;
+pub mod archetype;
+pub mod behaviour;
+pub mod spawner;
+pub mod split;
+pub mod wave;
 
 #[derive(Default)]
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (dot::init_resource,)).add_systems(
-            Update,
-            (
-                behaviour::attract::activate.run_if(on_timer(Duration::from_millis(200))),
-                behaviour::attract::follow.run_if(on_timer(Duration::from_millis(200))),
-            ),
-        );
+        app.init_asset::<EnemyArchetype>()
+            .register_asset_loader(EnemyArchetypeLoader)
+            .init_asset::<WavePlan>()
+            .register_asset_loader(WavePlanLoader)
+            .register_type::<behaviour::Ai>()
+            .register_type::<behaviour::Patrol>()
+            .register_type::<behaviour::Chase>()
+            .register_type::<behaviour::Attack>()
+            .register_type::<behaviour::Aim>()
+            .register_type::<behaviour::Flee>()
+            .register_type::<behaviour::Flock>()
+            .register_type::<behaviour::Leash>()
+            .register_type::<behaviour::Perception>()
+            .register_type::<behaviour::Think>()
+            .register_type::<behaviour::think::ThinkBudget>()
+            .register_type::<spawner::Spawner>()
+            .register_type::<split::Split>()
+            .register_type::<split::Generation>()
+            .register_type::<wave::WaveDirector>()
+            .init_resource::<behaviour::think::ThinkBudget>()
+            .register_diagnostic(Diagnostic::new(behaviour::think::AI_TICKS))
+            .add_systems(Startup, (spawner::init_resource, wave::init_resource))
+            .add_systems(
+                Update,
+                (
+                    (archetype::apply_archetypes, archetype::advance_phases).chain(),
+                    (archetype::drop_archetypes, split::split)
+                        .after(damage::apply_damage)
+                        .before(damage::despawn_on_fatal_damage),
+                    spawner::spawn_enemies,
+                    spawner::finish_telegraphs,
+                    (wave::toggle_arena, wave::direct_waves).chain(),
+                    behaviour::perception::hear,
+                    behaviour::aim::aim,
+                    behaviour::attack::fire.before(crate::weapon::shoot_active_weapon),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    behaviour::think::schedule,
+                    behaviour::perception::see,
+                    behaviour::decide,
+                    (
+                        behaviour::idle,
+                        behaviour::patrol::patrol,
+                        behaviour::chase::chase,
+                        behaviour::chase::investigate,
+                        behaviour::attack::attack,
+                        behaviour::flee::flee,
+                        behaviour::leash::return_home,
+                    ),
+                    behaviour::flock::flock,
+                )
+                    .chain()
+                    .before(crate::weapon::shoot_active_weapon),
+            );
     }
 }
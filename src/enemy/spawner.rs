@@ -0,0 +1,142 @@
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use rand::Rng as _;
+
+use super::archetype::{Archetype, EnemyArchetype};
+use crate::{
+    particles::{effect::ParticleEffect, EmitParticles},
+    player::PlayerShip,
+};
+
+/// Spawns enemies of an archetype at random points within `radius`, one every `interval`, while
+/// fewer than `max_alive` of them are alive. Each spawn is announced by a telegraph effect
+/// `telegraph` before the enemy appears.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+#[require(Transform)]
+pub struct Spawner {
+    pub archetype: Handle<EnemyArchetype>,
+    pub interval: Duration,
+    pub radius: f32,
+    pub max_alive: usize,
+    /// Enemies left to spawn, or None to spawn forever. The spawner despawns once it has
+    /// spawned them all and they are dead.
+    pub remaining: Option<u32>,
+    /// Only spawn while a player is within this distance
+    pub activation_range: Option<f32>,
+    pub telegraph: Duration,
+    pub last_spawn: Option<Duration>,
+}
+
+impl Spawner {
+    pub fn new(archetype: Handle<EnemyArchetype>) -> Self {
+        Self {
+            archetype,
+            interval: Duration::from_secs(2),
+            radius: 5.,
+            max_alive: 10,
+            remaining: None,
+            activation_range: None,
+            telegraph: Duration::from_secs(1),
+            last_spawn: None,
+        }
+    }
+}
+
+/// The [Spawner] an enemy came from
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SpawnedBy(pub Entity);
+
+/// An enemy announced by its spawner, that turns into the archetype at `deadline`
+#[derive(Component, Debug)]
+pub struct Telegraph {
+    pub archetype: Handle<EnemyArchetype>,
+    pub deadline: Duration,
+}
+
+#[derive(Resource)]
+pub struct SpawnerResources {
+    telegraph_effect: Handle<ParticleEffect>,
+}
+
+pub fn init_resource(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(SpawnerResources {
+        telegraph_effect: asset_server.load("particles/spawn.particles.ron"),
+    });
+}
+
+pub fn spawn_enemies(
+    mut spawners: Query<(Entity, &mut Spawner, &GlobalTransform)>,
+    spawned: Query<&SpawnedBy>,
+    players: Query<&GlobalTransform, With<PlayerShip>>,
+    res: Res<SpawnerResources>,
+    time: Res<Time>,
+    mut particles: EventWriter<EmitParticles>,
+    mut commands: Commands,
+) {
+    let mut alive = HashMap::<Entity, usize>::new();
+    for SpawnedBy(spawner) in spawned.iter() {
+        *alive.entry(*spawner).or_default() += 1;
+    }
+
+    let mut rng = rand::rng();
+
+    for (entity, mut spawner, transform) in spawners.iter_mut() {
+        let alive = alive.get(&entity).copied().unwrap_or(0);
+
+        if spawner.remaining == Some(0) {
+            if alive == 0 {
+                commands.entity(entity).despawn_recursive();
+            }
+            continue;
+        }
+
+        let ready = spawner
+            .last_spawn
+            .is_none_or(|last_spawn| time.elapsed() - last_spawn >= spawner.interval);
+        let active = spawner.activation_range.is_none_or(|range| {
+            players
+                .iter()
+                .any(|player| player.translation().distance(transform.translation()) <= range)
+        });
+        if !ready || !active || alive >= spawner.max_alive {
+            continue;
+        }
+
+        let offset = Vec2::from_angle(rng.random_range(0. ..std::f32::consts::TAU))
+            * rng.random_range(0. ..=spawner.radius);
+        let position = transform.translation() + offset.extend(0.);
+
+        commands.spawn((Transform::from_translation(position), SpawnedBy(entity), Telegraph {
+            archetype: spawner.archetype.clone(),
+            deadline: time.elapsed() + spawner.telegraph,
+        }));
+        particles.send(EmitParticles {
+            effect: res.telegraph_effect.clone(),
+            position,
+            velocity: Vec3::ZERO,
+        });
+
+        spawner.last_spawn = Some(time.elapsed());
+        if let Some(remaining) = spawner.remaining.as_mut() {
+            *remaining -= 1;
+        }
+    }
+}
+
+pub fn finish_telegraphs(
+    telegraphs: Query<(Entity, &Telegraph)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, telegraph) in telegraphs.iter() {
+        if time.elapsed() >= telegraph.deadline {
+            commands
+                .entity(entity)
+                .remove::<Telegraph>()
+                .insert(Archetype(telegraph.archetype.clone()));
+        }
+    }
+}
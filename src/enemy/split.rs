@@ -0,0 +1,122 @@
+use std::f32::consts::TAU;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{archetype::Archetype, spawner::SpawnedBy};
+use crate::weapon::damage::FatalDamage;
+
+/// Splits into `count` smaller copies of the same archetype on death, asteroid style. The copies
+/// fly apart from where the enemy died and keep its momentum.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Debug)]
+#[reflect(Component)]
+pub struct Split {
+    pub count: u32,
+    /// Size of the copies relative to the enemy
+    pub scale: f32,
+    /// Health of the copies relative to the enemy
+    pub health: f32,
+    /// Max speed of the copies relative to the enemy
+    pub speed: f32,
+    /// Speed at which the copies fly apart, in units per second
+    pub spread: f32,
+    /// How many times the copies split again
+    pub generations: u32,
+}
+
+impl Split {
+    /// Factors for the health and max speed of an enemy after `generation` splits
+    pub fn factors(&self, generation: u32) -> (f32, f32) {
+        let generation = generation as i32;
+        (self.health.powi(generation), self.speed.powi(generation))
+    }
+
+    /// Outward velocities of the copies, evenly spread around a random angle
+    pub fn velocities(&self, start: f32) -> impl Iterator<Item = Vec3> + '_ {
+        (0..self.count).map(move |index| {
+            let angle = start + TAU * index as f32 / self.count as f32;
+            (Vec2::from_angle(angle) * self.spread).extend(0.)
+        })
+    }
+}
+
+/// Number of times the enemy's ancestors split
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct Generation(pub u32);
+
+pub fn split(
+    mut reader: EventReader<FatalDamage>,
+    enemies: Query<(
+        &Split,
+        &Archetype,
+        &Transform,
+        Option<&Generation>,
+        Option<&LinearVelocity>,
+        Option<&SpawnedBy>,
+    )>,
+    mut commands: Commands,
+) {
+    for FatalDamage { target, .. } in reader.read() {
+        let Ok((split, archetype, transform, generation, velocity, spawned_by)) =
+            enemies.get(*target)
+        else {
+            continue;
+        };
+
+        let generation = generation.map_or(0, |generation| generation.0);
+        if generation >= split.generations {
+            continue;
+        }
+
+        let velocity = velocity.map_or(Vec3::ZERO, |velocity| velocity.0);
+        let start = rand::random::<f32>() * TAU;
+
+        for outward in split.velocities(start) {
+            let mut copy = commands.spawn((
+                archetype.clone(),
+                Generation(generation + 1),
+                Transform {
+                    translation: transform.translation + outward.normalize_or_zero(),
+                    scale: transform.scale * split.scale,
+                    ..*transform
+                },
+                LinearVelocity(velocity + outward),
+            ));
+
+            // waves aren't over until all pieces are dead
+            if let Some(spawned_by) = spawned_by {
+                copy.insert(*spawned_by);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_copies() {
+        let split = Split {
+            count: 4,
+            scale: 0.5,
+            health: 0.5,
+            speed: 1.5,
+            spread: 10.,
+            generations: 2,
+        };
+
+        assert_eq!(split.factors(0), (1., 1.));
+        assert_eq!(split.factors(2), (0.25, 2.25));
+
+        let velocities: Vec<_> = split.velocities(0.).collect();
+        assert_eq!(velocities.len(), 4);
+        assert!(velocities[1].abs_diff_eq(Vec3::Y * 10., 1e-4));
+        assert!(velocities
+            .iter()
+            .sum::<Vec3>()
+            .abs_diff_eq(Vec3::ZERO, 1e-4));
+    }
+}
@@ -0,0 +1,315 @@
+//! Arena survival: the [WaveDirector] sends escalating waves of enemies at the players, as
+//! planned by a `.waves.ron` file.
+//!
+//! Start the game with `--arena` to fight the waves right away, or press F2 to start and stop
+//! them during play.
+
+use avian3d::prelude::LinearVelocity;
+use bevy::{
+    asset::AssetLoader,
+    prelude::*,
+    utils::{ConditionalSendFuture, Duration},
+};
+use rand::Rng as _;
+use serde::{Deserialize, Serialize};
+
+use super::spawner::Spawner;
+use crate::player::PlayerShip;
+
+/// Waves of an arena, loaded from a `.waves.ron` file. After the last wave, the waves repeat
+/// with `escalation` times as many enemies on every repetition.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone)]
+pub struct WavePlan {
+    pub waves: Vec<Wave>,
+    pub escalation: f32,
+    /// Distance from the players at which groups spawn
+    pub spawn_distance: f32,
+    /// Seconds between the telegraph and the appearance of every enemy
+    pub telegraph: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Wave {
+    /// Seconds of rest before the wave
+    pub rest: f32,
+    pub groups: Vec<WaveGroup>,
+}
+
+/// Enemies of one archetype that spawn together
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WaveGroup {
+    pub archetype: String,
+    pub count: u32,
+    /// Seconds after the start of the wave
+    #[serde(default)]
+    pub delay: f32,
+    /// Seconds between two enemies of the group
+    #[serde(default = "WaveGroup::default_interval")]
+    pub interval: f32,
+}
+
+impl WaveGroup {
+    fn default_interval() -> f32 {
+        0.2
+    }
+}
+
+impl WavePlan {
+    /// The wave with the given number, counting from 0, and how many times it repeated
+    pub fn wave(&self, number: usize) -> Option<(&Wave, u32)> {
+        let count = self.waves.len();
+        (count > 0).then(|| (&self.waves[number % count], (number / count) as u32))
+    }
+
+    /// Checks that all times are valid durations
+    pub fn validate(&self) -> Result<(), String> {
+        let seconds = |name: &str, value: f32| match value.is_finite() && value >= 0. {
+            true => Ok(()),
+            false => Err(format!("{name} must be a non-negative number of seconds, got {value}")),
+        };
+
+        seconds("telegraph", self.telegraph)?;
+        for (number, wave) in self.waves.iter().enumerate() {
+            seconds(&format!("rest of wave {}", number + 1), wave.rest)?;
+
+            for group in wave.groups.iter() {
+                let name = format!("{} in wave {}", group.archetype, number + 1);
+                seconds(&format!("delay of {name}"), group.delay)?;
+                seconds(&format!("interval of {name}"), group.interval)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number of enemies in a group, after `repetitions` repetitions of its wave
+    pub fn group_count(&self, group: &WaveGroup, repetitions: u32) -> u32 {
+        (group.count as f32 * self.escalation.powi(repetitions as i32)).round() as u32
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WavePlanLoaderError {
+    #[error("failed to read wave plan: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse wave plan: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid wave plan: {0}")]
+    Invalid(String),
+}
+
+pub struct WavePlanLoader;
+impl AssetLoader for WavePlanLoader {
+    type Asset = WavePlan;
+    type Settings = ();
+    type Error = WavePlanLoaderError;
+
+    fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut bevy::asset::LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+
+            let plan: WavePlan = ron::de::from_bytes(&bytes)?;
+            plan.validate().map_err(WavePlanLoaderError::Invalid)?;
+
+            Ok(plan)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
+
+/// Runs the waves of a [WavePlan] while `enabled`
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct WaveDirector {
+    pub enabled: bool,
+    pub plan: Handle<WavePlan>,
+    /// The current wave, counting from 0
+    pub wave: usize,
+    pub state: WaveState,
+}
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub enum WaveState {
+    /// Waiting for the rest before the current wave to end, with the elapsed time at its start
+    Rest { started: Option<Duration> },
+    /// Spawning groups, with the elapsed time at the start of the wave
+    Spawning { started: Duration, next_group: usize },
+    /// Waiting for the enemies of the wave to die
+    Fighting,
+}
+
+impl WaveDirector {
+    /// Starts over from the first wave
+    pub fn start(&mut self) {
+        self.enabled = true;
+        self.wave = 0;
+        self.state = default();
+    }
+}
+
+impl Default for WaveState {
+    fn default() -> Self {
+        WaveState::Rest { started: None }
+    }
+}
+
+/// A [Spawner] placed by the [WaveDirector]
+#[derive(Component)]
+pub struct WaveSpawner;
+
+pub fn init_resource(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(WaveDirector {
+        enabled: std::env::args().any(|arg| arg == "--arena"),
+        plan: asset_server.load("waves/arena.waves.ron"),
+        wave: 0,
+        state: default(),
+    });
+}
+
+/// Starts the arena with F2, moving the players back to the middle, or stops it again
+pub fn toggle_arena(
+    input: Res<ButtonInput<KeyCode>>,
+    mut director: ResMut<WaveDirector>,
+    wave_spawners: Query<Entity, With<WaveSpawner>>,
+    mut players: Query<(&mut Transform, &mut LinearVelocity), With<PlayerShip>>,
+    mut commands: Commands,
+) {
+    if !input.just_pressed(KeyCode::F2) {
+        return;
+    }
+
+    if director.enabled {
+        info!("Arena stopped");
+        director.enabled = false;
+        for spawner in wave_spawners.iter() {
+            commands.entity(spawner).despawn();
+        }
+        return;
+    }
+
+    info!("Arena started");
+    director.start();
+    for (mut transform, mut velocity) in players.iter_mut() {
+        *transform = default();
+        velocity.0 = Vec3::ZERO;
+    }
+}
+
+pub fn direct_waves(
+    mut director: ResMut<WaveDirector>,
+    plans: Res<Assets<WavePlan>>,
+    wave_spawners: Query<(), With<WaveSpawner>>,
+    players: Query<&GlobalTransform, With<PlayerShip>>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    if !director.enabled {
+        return;
+    }
+
+    let Some(plan) = plans.get(&director.plan) else {
+        return;
+    };
+    let Some((wave, repetitions)) = plan.wave(director.wave) else {
+        return;
+    };
+
+    match director.state {
+        WaveState::Rest { started } => {
+            let Some(started) = started else {
+                director.state = WaveState::Rest { started: Some(time.elapsed()) };
+                return;
+            };
+
+            if time.elapsed() - started >= Duration::from_secs_f32(wave.rest) {
+                info!("Wave {} started", director.wave + 1);
+                director.state = WaveState::Spawning {
+                    started: time.elapsed(),
+                    next_group: 0,
+                };
+            }
+        }
+        WaveState::Spawning { started, next_group } => {
+            let Some(group) = wave.groups.get(next_group) else {
+                director.state = WaveState::Fighting;
+                return;
+            };
+            if time.elapsed() - started < Duration::from_secs_f32(group.delay) {
+                return;
+            }
+
+            // around the middle of the players, from a random direction
+            let players: Vec<_> = players.iter().map(|player| player.translation()).collect();
+            let center = players.iter().sum::<Vec3>() / players.len().max(1) as f32;
+            let direction = Vec2::from_angle(rand::rng().random_range(0. ..std::f32::consts::TAU));
+
+            commands.spawn((
+                Name::new(format!("Wave {} spawner", director.wave + 1)),
+                WaveSpawner,
+                Transform::from_translation(center + (direction * plan.spawn_distance).extend(0.)),
+                Spawner {
+                    interval: Duration::from_secs_f32(group.interval),
+                    max_alive: usize::MAX,
+                    remaining: Some(plan.group_count(group, repetitions)),
+                    telegraph: Duration::from_secs_f32(plan.telegraph),
+                    ..Spawner::new(asset_server.load(&group.archetype))
+                },
+            ));
+
+            director.state = WaveState::Spawning { started, next_group: next_group + 1 };
+        }
+        WaveState::Fighting => {
+            if wave_spawners.is_empty() {
+                info!("Wave {} cleared", director.wave + 1);
+                director.wave += 1;
+                director.state = default();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalating_waves() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/waves/arena.waves.ron");
+        let mut plan: WavePlan = ron::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(plan.validate(), Ok(()));
+        let count = plan.waves.len();
+        assert!(count > 0);
+
+        let (first, repetitions) = plan.wave(0).unwrap();
+        assert_eq!(repetitions, 0);
+        assert_eq!(plan.group_count(&first.groups[0], 0), first.groups[0].count);
+
+        let (repeated, repetitions) = plan.wave(count).unwrap();
+        assert_eq!(repetitions, 1);
+        assert!(plan.group_count(&repeated.groups[0], 1) > first.groups[0].count);
+
+        for wave in plan.waves.iter() {
+            for group in wave.groups.iter() {
+                let archetype =
+                    format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), group.archetype);
+                assert!(std::path::Path::new(&archetype).exists(), "{archetype} not found");
+            }
+        }
+
+        plan.waves[0].groups[0].delay = -1.;
+        assert!(plan.validate().is_err());
+        plan.waves[0].groups[0].delay = 0.;
+        plan.telegraph = f32::NAN;
+        assert!(plan.validate().is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Instanced drawing of line meshes.
+//!
+//! Every entity with [InstancedLines] is grouped by its mesh, and each group is drawn with a
+//! single instanced draw call. The mesh is the shared segment buffer, and each instance carries
+//! its own transform, colour and line width.
+
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    ecs::{
+        query::QueryItem,
+        system::{lifetimeless::*, SystemParamItem},
+    },
+    pbr::{
+        MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup,
+    },
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{
+            allocator::MeshAllocator, MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo,
+        },
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand as _, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+            RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+        },
+        render_resource::*,
+        renderer::RenderDevice,
+        sync_world::MainEntity,
+        view::{ExtractedView, NoFrustumCulling, VisibilitySystems},
+        Render, RenderApp, RenderSet,
+    },
+    transform::TransformSystem,
+    utils::HashMap,
+};
+use bytemuck::{Pod, Zeroable};
+
+use crate::line_material::LineMaterial;
+
+const SHADER_ASSET_PATH: &str = "shaders/instanced_lines.wgsl";
+
+pub struct InstancedLinesPlugin;
+impl Plugin for InstancedLinesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractComponentPlugin::<LineInstanceBatch>::default())
+            .add_systems(
+                PostUpdate,
+                gather_instances
+                    .after(TransformSystem::TransformPropagate)
+                    .after(VisibilitySystems::VisibilityPropagate),
+            );
+
+        app.sub_app_mut(RenderApp)
+            .add_render_command::<Transparent3d, DrawInstancedLines>()
+            .init_resource::<SpecializedMeshPipelines<InstancedLinesPipeline>>()
+            .add_systems(
+                Render,
+                (
+                    queue_instanced_lines.in_set(RenderSet::QueueMeshes),
+                    prepare_instance_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<InstancedLinesPipeline>();
+    }
+}
+
+/// Draw a line mesh (see [crate::line_material::LineList]) through the instanced pipeline.
+/// All entities sharing the same mesh are drawn with one draw call.
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct InstancedLines {
+    pub mesh: Handle<Mesh>,
+    /// Multiplied with the vertex colours of the mesh
+    pub color: LinearRgba,
+    pub line_width: f32,
+}
+
+impl InstancedLines {
+    pub fn new<C: Into<LinearRgba>>(mesh: Handle<Mesh>, color: C) -> Self {
+        Self {
+            mesh,
+            color: color.into(),
+            line_width: LineMaterial::DEFAULT_LINE_WIDTH,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct LineInstanceData {
+    world_from_local: [[f32; 4]; 4],
+    color: [f32; 4],
+    line_width: f32,
+}
+
+/// All visible instances of one mesh. Lives on a separate entity that holds the shared mesh.
+#[derive(Component, Clone, Deref)]
+struct LineInstanceBatch(Vec<LineInstanceData>);
+
+impl ExtractComponent for LineInstanceBatch {
+    type QueryData = &'static LineInstanceBatch;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        (!item.is_empty()).then(|| item.clone())
+    }
+}
+
+fn gather_instances(
+    instances: Query<(&InstancedLines, &GlobalTransform, &InheritedVisibility)>,
+    mut batches: Query<(&Mesh3d, &mut LineInstanceBatch)>,
+    mut commands: Commands,
+) {
+    let mut grouped = HashMap::<AssetId<Mesh>, (Handle<Mesh>, Vec<LineInstanceData>)>::default();
+
+    for (lines, transform, visibility) in instances.iter() {
+        if !visibility.get() {
+            continue;
+        }
+
+        grouped
+            .entry(lines.mesh.id())
+            .or_insert_with(|| (lines.mesh.clone(), vec![]))
+            .1
+            .push(LineInstanceData {
+                world_from_local: transform.compute_matrix().to_cols_array_2d(),
+                color: lines.color.to_f32_array(),
+                line_width: lines.line_width,
+            });
+    }
+
+    for (mesh, mut batch) in batches.iter_mut() {
+        batch.0 = grouped
+            .remove(&mesh.id())
+            .map(|(_, data)| data)
+            .unwrap_or_default();
+    }
+
+    for (mesh, data) in grouped.into_values() {
+        commands.spawn((
+            Name::new("Line instance batch"),
+            Mesh3d(mesh),
+            LineInstanceBatch(data),
+            // instances are spread over the whole map, so the batch itself can't be culled
+            NoFrustumCulling,
+        ));
+    }
+}
+
+#[derive(Component)]
+struct InstanceBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+fn prepare_instance_buffers(
+    query: Query<(Entity, &LineInstanceBatch)>,
+    render_device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    for (entity, batch) in query.iter() {
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("line instance buffer"),
+            contents: bytemuck::cast_slice(batch.as_slice()),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+
+        commands
+            .entity(entity)
+            .insert(InstanceBuffer { buffer, length: batch.len() });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_instanced_lines(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    pipeline: Res<InstancedLinesPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<InstancedLinesPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    batches: Query<(Entity, &MainEntity), With<LineInstanceBatch>>,
+    mut render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(Entity, &ExtractedView, &Msaa)>,
+) {
+    let draw_function = draw_functions.read().id::<DrawInstancedLines>();
+
+    for (view_entity, view, msaa) in views.iter() {
+        let Some(phase) = render_phases.get_mut(&view_entity) else {
+            continue;
+        };
+
+        let view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr);
+        let rangefinder = view.rangefinder3d();
+
+        for (entity, main_entity) in batches.iter() {
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity)
+            else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+
+            let key =
+                view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let pipeline = match pipelines.specialize(&pipeline_cache, &pipeline, key, &mesh.layout)
+            {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("failed to specialize instanced line pipeline: {err}");
+                    continue;
+                }
+            };
+
+            phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
+        }
+    }
+}
+
+#[derive(Resource)]
+struct InstancedLinesPipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+}
+
+impl FromWorld for InstancedLinesPipeline {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            shader: world.load_asset(SHADER_ASSET_PATH),
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for InstancedLinesPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+
+        let vertex_layout = layout.0.get_layout(&[
+            LineMaterial::ATTRIBUTE_POSITION_A.at_shader_location(0),
+            LineMaterial::ATTRIBUTE_POSITION_B.at_shader_location(1),
+            LineMaterial::ATTRIBUTE_COLOR_A.at_shader_location(2),
+            LineMaterial::ATTRIBUTE_COLOR_B.at_shader_location(3),
+        ])?;
+
+        let vec4_size = VertexFormat::Float32x4.size();
+        let instance_layout = VertexBufferLayout {
+            array_stride: size_of::<LineInstanceData>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                // world_from_local, one column per attribute
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 4,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: vec4_size,
+                    shader_location: 5,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: vec4_size * 2,
+                    shader_location: 6,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: vec4_size * 3,
+                    shader_location: 7,
+                },
+                // color
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: vec4_size * 4,
+                    shader_location: 8,
+                },
+                // line_width
+                VertexAttribute {
+                    format: VertexFormat::Float32,
+                    offset: vec4_size * 5,
+                    shader_location: 9,
+                },
+            ],
+        };
+
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.buffers = vec![vertex_layout, instance_layout];
+        descriptor.fragment.as_mut().unwrap().shader = self.shader.clone();
+
+        Ok(descriptor)
+    }
+}
+
+type DrawInstancedLines =
+    (SetItemPipeline, SetMeshViewBindGroup<0>, SetMeshBindGroup<1>, DrawMeshInstanced);
+
+struct DrawMeshInstanced;
+impl<P: PhaseItem> RenderCommand<P> for DrawMeshInstanced {
+    type Param = (SRes<RenderAssets<RenderMesh>>, SRes<RenderMeshInstances>, SRes<MeshAllocator>);
+    type ViewQuery = ();
+    type ItemQuery = Read<InstanceBuffer>;
+
+    #[inline]
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        instance_buffer: Option<&'w InstanceBuffer>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity())
+        else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(instance_buffer) = instance_buffer else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertex_buffer_slice) =
+            mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id)
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_vertex_buffer(0, vertex_buffer_slice.buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..));
+
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed { index_format, count } => {
+                let Some(index_buffer_slice) =
+                    mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id)
+                else {
+                    return RenderCommandResult::Skip;
+                };
+
+                pass.set_index_buffer(index_buffer_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(
+                    index_buffer_slice.range.start..(index_buffer_slice.range.start + count),
+                    vertex_buffer_slice.range.start as i32,
+                    0..instance_buffer.length as u32,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertex_buffer_slice.range, 0..instance_buffer.length as u32);
+            }
+        }
+
+        RenderCommandResult::Success
+    }
+}
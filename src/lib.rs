@@ -0,0 +1,20 @@
+//! The game, shared by the `lasergame` binary and the model tools in `src/bin`.
+
+#![feature(array_windows)]
+#![feature(extract_if)]
+#![warn(clippy::unused_trait_names)]
+
+pub mod assets;
+pub mod crt;
+pub mod enemy;
+pub mod instanced_lines;
+pub mod line_material;
+pub mod mapgen;
+pub mod misc;
+pub mod particles;
+pub mod player;
+pub mod shapes;
+pub mod team;
+pub mod trail;
+pub mod utils;
+pub mod weapon;
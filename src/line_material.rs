@@ -39,11 +39,13 @@ impl LineMaterial {
     pub const ATTRIBUTE_COLOR_B: MeshVertexAttribute =
         MeshVertexAttribute::new("LineMaterial_ColorB", 986361504, VertexFormat::Float32x4);
 
+    pub const DEFAULT_LINE_WIDTH: f32 = 100.;
+
     pub fn new<C: Into<LinearRgba>>(color: C) -> Self {
         Self {
             uniform: LineMaterialUniform {
                 color: color.into(),
-                line_width: 100.,
+                line_width: Self::DEFAULT_LINE_WIDTH,
                 depth_bias: 0.,
                 line_scale: 1.,
                 gap_scale: 1.,
@@ -146,7 +148,7 @@ impl From<LineStrip> for Mesh {
 impl From<LineStrip> for LineList {
     fn from(strip: LineStrip) -> Self {
         let lines = strip.points.array_windows().copied().collect();
-        let colors = vec![[LinearRgba::WHITE; 2]; strip.points.len() - 1];
+        let colors = vec![[LinearRgba::WHITE; 2]; strip.points.len().saturating_sub(1)];
 
         LineList { lines, colors }
     }
@@ -167,4 +169,12 @@ mod tests {
         assert_eq!(list.lines, vec![[Vec3::ZERO, Vec3::X], [Vec3::X, Vec3::Y]]);
         assert_eq!(list.colors, vec![[LinearRgba::WHITE; 2]; 2]);
     }
+
+    #[test]
+    fn empty_line_strip_into_list() {
+        let list = LineList::from(LineStrip { points: vec![] });
+
+        assert!(list.lines.is_empty());
+        assert!(list.colors.is_empty());
+    }
 }
@@ -1,5 +1,3 @@
-#![feature(array_windows)]
-#![feature(extract_if)]
 #![warn(clippy::unused_trait_names)]
 
 use avian3d::{prelude::Gravity, PhysicsPlugins};
@@ -14,25 +12,13 @@ use bevy::{
 };
 use bevy_egui::{egui, EguiContext, EguiPlugin};
 use bevy_inspector_egui::{bevy_egui, bevy_inspector, DefaultInspectorConfigPlugin};
-use enemy::EnemyPlugin;
-use team::Team;
-
-use self::{
-    assets::AssetsPlugin, line_material::LineMaterial, mapgen::MapgenPlugin, misc::CameraOffset,
-    player::PlayerPlugin, weapon::WeaponPlugin,
+use lasergame::{
+    assets::AssetsPlugin, crt, crt::CrtPlugin, enemy::EnemyPlugin,
+    instanced_lines::InstancedLinesPlugin, line_material::LineMaterial, mapgen::MapgenPlugin, misc,
+    misc::CameraOffset, particles::ParticlePlugin, player::PlayerPlugin, team, team::Team,
+    trail::TrailPlugin, weapon::WeaponPlugin,
 };
 
-mod assets;
-mod enemy;
-mod line_material;
-mod mapgen;
-mod misc;
-mod player;
-mod shapes;
-mod team;
-mod utils;
-mod weapon;
-
 fn main() {
     App::new()
         .add_plugins((
@@ -43,7 +29,7 @@ fn main() {
                 })
                 .set(LogPlugin {
                     level: bevy::log::Level::INFO,
-                    filter: "lasergame=trace,wgpu=warn,big_brain=debug".to_string(),
+                    filter: "lasergame=trace,wgpu=warn".to_string(),
                     ..default()
                 })
                 .set(WindowPlugin {
@@ -62,10 +48,20 @@ fn main() {
             EguiPlugin,
             DefaultInspectorConfigPlugin,
         ))
-        .add_plugins((AssetsPlugin, EnemyPlugin, MapgenPlugin, PlayerPlugin, WeaponPlugin))
+        .add_plugins((
+            AssetsPlugin,
+            CrtPlugin,
+            EnemyPlugin,
+            MapgenPlugin,
+            ParticlePlugin,
+            PlayerPlugin,
+            TrailPlugin,
+            WeaponPlugin,
+        ))
         .insert_resource(Gravity::ZERO)
-        .add_plugins(MaterialPlugin::<LineMaterial>::default())
+        .add_plugins((MaterialPlugin::<LineMaterial>::default(), InstancedLinesPlugin))
         .register_type::<CameraOffset>()
+        .register_type::<misc::FadeCurve>()
         .register_type::<LineMaterial>()
         .register_type::<Team>()
         .register_asset_reflect::<LineMaterial>()
@@ -76,6 +72,7 @@ fn main() {
                 team::propagate_team,
                 misc::target_movement,
                 misc::expire,
+                misc::fade_out,
                 debug_overlay,
                 close_on_esc,
             ),
@@ -92,6 +89,7 @@ fn init_camera(mut commands: Commands) {
             ..default()
         },
         Bloom::NATURAL,
+        crt::CrtSettings::default(),
     ));
 }
 
@@ -71,9 +71,10 @@ fn debug_mapgen(
                 if ui.button("Reset player").clicked() {
                     commands.queue(|world: &mut World| {
                         let mut query = world.query_filtered::<(&mut Transform, &mut CameraOffset), With<PlayerShip>>();
-                        let (mut pos, mut cam_offset) = query.single_mut(world);
-                        *pos = default();
-                        *cam_offset = default();
+                        for (mut pos, mut cam_offset) in query.iter_mut(world) {
+                            *pos = default();
+                            *cam_offset = default();
+                        }
                     });
                 }
 
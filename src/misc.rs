@@ -1,7 +1,8 @@
 use avian3d::prelude::*;
 use bevy::{prelude::*, utils::Duration};
+use serde::{Deserialize, Serialize};
 
-use crate::team::Team;
+use crate::{instanced_lines::InstancedLines, team::Team};
 
 pub const LOCKED_AXES: LockedAxes = LockedAxes::new()
     .lock_rotation_x()
@@ -28,7 +29,7 @@ impl GameLayer {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
 #[require(TargetMovement)]
 pub struct MovementSpeed {
     pub max_speed: f32,
@@ -80,3 +81,56 @@ pub fn expire(mut query: Query<(Entity, &Expire)>, time: Res<Time>, mut commands
         }
     }
 }
+
+/// Fades the colour of [InstancedLines] to black, usually paired with [Expire].
+#[derive(Component)]
+pub struct FadeOut {
+    pub color: LinearRgba,
+    pub curve: FadeCurve,
+    pub start: Duration,
+    pub duration: Duration,
+}
+pub fn fade_out(mut query: Query<(&FadeOut, &mut InstancedLines)>, time: Res<Time>) {
+    for (fade, mut lines) in query.iter_mut() {
+        let t =
+            time.elapsed().saturating_sub(fade.start).as_secs_f32() / fade.duration.as_secs_f32();
+        lines.color = fade.color * fade.curve.brightness(t);
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub enum FadeCurve {
+    #[default]
+    Linear,
+    /// Brightness falls off with `(1 - t)^exponent`. Larger exponents give a shorter glow.
+    Power(f32),
+    /// Full brightness until the end
+    None,
+}
+
+impl FadeCurve {
+    /// Brightness at `t`, where 0 is the start of the fade and 1 is the end.
+    pub fn brightness(&self, t: f32) -> f32 {
+        let remaining = (1. - t).clamp(0., 1.);
+
+        match *self {
+            FadeCurve::Linear => remaining,
+            FadeCurve::Power(exponent) => remaining.powf(exponent),
+            FadeCurve::None => 1.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_curves() {
+        assert_eq!(FadeCurve::Linear.brightness(0.), 1.);
+        assert_eq!(FadeCurve::Linear.brightness(0.25), 0.75);
+        assert_eq!(FadeCurve::Linear.brightness(2.), 0.);
+        assert_eq!(FadeCurve::Power(2.).brightness(0.5), 0.25);
+        assert_eq!(FadeCurve::None.brightness(0.9), 1.);
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::{asset::AssetLoader, prelude::*, utils::ConditionalSendFuture};
+use serde::{Deserialize, Serialize};
+
+use crate::misc::FadeCurve;
+
+/// A burst of particles, loaded from a `.particles.ron` file.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone)]
+pub struct ParticleEffect {
+    pub emitters: Vec<Emitter>,
+}
+
+/// Spawns `count` particles of one shape at once. Ranges are `(min, max)` and sampled
+/// uniformly per particle.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Emitter {
+    pub shape: ParticleShape,
+    pub count: u32,
+    /// Seconds
+    pub lifetime: (f32, f32),
+    /// Units per second, in a random direction on the xy plane
+    pub speed: (f32, f32),
+    /// Length of line particles, or starting radius of rings
+    pub size: (f32, f32),
+    /// Fraction of velocity lost per second
+    #[serde(default)]
+    pub drag: f32,
+    pub color: LinearRgba,
+    #[serde(default)]
+    pub fade: FadeCurve,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ParticleShape {
+    /// Short streaks stretched along their direction of travel
+    Spark,
+    /// Tumbling fragments with a random orientation and spin
+    Debris {
+        /// Radians per second
+        spin: (f32, f32),
+    },
+    /// An expanding circle. `speed` is how fast its radius grows.
+    Ring { segments: u32 },
+}
+
+impl ParticleEffect {
+    /// Checks that all ranges are finite, and lifetimes aren't negative
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, emitter) in self.emitters.iter().enumerate() {
+            let spin = match emitter.shape {
+                ParticleShape::Debris { spin } => spin,
+                _ => (0., 0.),
+            };
+
+            for (name, (min, max)) in [
+                ("lifetime", emitter.lifetime),
+                ("speed", emitter.speed),
+                ("size", emitter.size),
+                ("spin", spin),
+            ] {
+                if !min.is_finite() || !max.is_finite() {
+                    return Err(format!("emitter {index}: {name} ({min}, {max}) is not finite"));
+                }
+            }
+
+            let (min, max) = emitter.lifetime;
+            if min < 0. || max < 0. {
+                return Err(format!("emitter {index}: lifetime ({min}, {max}) is negative"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParticleEffectLoaderError {
+    #[error("failed to read particle effect: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse particle effect: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid particle effect: {0}")]
+    Invalid(String),
+}
+
+pub struct ParticleEffectLoader;
+impl AssetLoader for ParticleEffectLoader {
+    type Asset = ParticleEffect;
+    type Settings = ();
+    type Error = ParticleEffectLoaderError;
+
+    fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut bevy::asset::LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        async move {
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes).await?;
+
+            let effect: ParticleEffect = ron::de::from_bytes(&bytes)?;
+            effect
+                .validate()
+                .map_err(ParticleEffectLoaderError::Invalid)?;
+
+            Ok(effect)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particles.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bundled_effects() {
+        for name in ["explosion", "hit_spark", "spawn"] {
+            let path =
+                format!("{}/assets/particles/{name}.particles.ron", env!("CARGO_MANIFEST_DIR"));
+            let source = std::fs::read_to_string(&path).unwrap();
+            let effect: ParticleEffect = ron::from_str(&source).unwrap();
+
+            assert!(!effect.emitters.is_empty(), "{path} has no emitters");
+            assert_eq!(effect.validate(), Ok(()), "{path}");
+        }
+    }
+
+    #[test]
+    fn invalid_lifetime() {
+        let mut effect = ParticleEffect {
+            emitters: vec![Emitter {
+                shape: ParticleShape::Spark,
+                count: 1,
+                lifetime: (0.2, 0.5),
+                speed: (1., 2.),
+                size: (1., 1.),
+                drag: 0.,
+                color: LinearRgba::WHITE,
+                fade: default(),
+            }],
+        };
+        assert_eq!(effect.validate(), Ok(()));
+
+        effect.emitters[0].lifetime = (-1., 0.5);
+        assert!(effect.validate().is_err());
+
+        effect.emitters[0].lifetime = (0.2, f32::INFINITY);
+        assert!(effect.validate().is_err());
+    }
+}
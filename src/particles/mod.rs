@@ -0,0 +1,277 @@
+//! Short-lived line particles. All particles are simulated on the CPU and drawn as a single
+//! [LineList] mesh with one [LineMaterial].
+
+use std::f32::consts::TAU;
+
+use avian3d::prelude::LinearVelocity;
+use bevy::{prelude::*, utils::Duration};
+use rand::Rng as _;
+
+use self::effect::{ParticleEffect, ParticleEffectLoader, ParticleShape};
+use crate::{
+    line_material::{LineList, LineMaterial},
+    misc::FadeCurve,
+    weapon::damage::{self, DamageEvent, FatalDamage},
+};
+
+pub mod effect;
+pub mod shatter;
+
+#[derive(Default)]
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ParticleEffect>()
+            .register_asset_loader(ParticleEffectLoader)
+            .init_resource::<Particles>()
+            .init_resource::<shatter::FragmentMeshes>()
+            .add_event::<EmitParticles>()
+            .add_systems(Startup, init_renderer)
+            .add_systems(
+                Update,
+                (
+                    (
+                        hit_effects,
+                        death_effects
+                            .after(damage::apply_damage)
+                            .before(damage::despawn_on_fatal_damage),
+                        shatter::shatter
+                            .after(damage::apply_damage)
+                            .before(damage::despawn_on_fatal_damage),
+                    ),
+                    emit,
+                    simulate,
+                    update_mesh,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Emit a [ParticleEffect] at the entity's position when it takes damage.
+#[derive(Component)]
+pub struct HitEffect(pub Handle<ParticleEffect>);
+
+/// Emit a [ParticleEffect] at the entity's position when it dies.
+#[derive(Component)]
+pub struct DeathEffect(pub Handle<ParticleEffect>);
+
+/// Spawn all particles of an effect at a position.
+#[derive(Event)]
+pub struct EmitParticles {
+    pub effect: Handle<ParticleEffect>,
+    pub position: Vec3,
+    /// Added to the velocity of every particle, e.g. the velocity of a dying enemy
+    pub velocity: Vec3,
+}
+
+#[derive(Resource, Default)]
+pub struct Particles(Vec<Particle>);
+
+struct Particle {
+    shape: ParticleShape,
+    position: Vec3,
+    velocity: Vec3,
+    /// Rotation around the z axis
+    angle: f32,
+    spin: f32,
+    size: f32,
+    drag: f32,
+    color: LinearRgba,
+    fade: FadeCurve,
+    spawned: Duration,
+    lifetime: Duration,
+}
+
+#[derive(Component)]
+struct ParticleRenderer;
+
+fn init_renderer(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LineMaterial>>,
+    mut commands: Commands,
+) {
+    let mesh = meshes.add(LineList { lines: vec![], colors: vec![] });
+    // particle colours live in the vertex colours, so the material itself stays white
+    let material = materials.add(LinearRgba::WHITE);
+
+    commands.spawn((
+        Name::new("Particles"),
+        ParticleRenderer,
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::default(),
+        Visibility::Hidden,
+    ));
+}
+
+fn hit_effects(
+    mut reader: EventReader<DamageEvent>,
+    targets: Query<(&HitEffect, &GlobalTransform)>,
+    mut writer: EventWriter<EmitParticles>,
+) {
+    for DamageEvent { target, .. } in reader.read() {
+        if let Ok((HitEffect(effect), transform)) = targets.get(*target) {
+            writer.send(EmitParticles {
+                effect: effect.clone(),
+                position: transform.translation(),
+                velocity: Vec3::ZERO,
+            });
+        }
+    }
+}
+
+fn death_effects(
+    mut reader: EventReader<FatalDamage>,
+    targets: Query<(&DeathEffect, &GlobalTransform, Option<&LinearVelocity>)>,
+    mut writer: EventWriter<EmitParticles>,
+) {
+    for FatalDamage { target, .. } in reader.read() {
+        if let Ok((DeathEffect(effect), transform, velocity)) = targets.get(*target) {
+            writer.send(EmitParticles {
+                effect: effect.clone(),
+                position: transform.translation(),
+                velocity: velocity.map_or(Vec3::ZERO, |v| v.0),
+            });
+        }
+    }
+}
+
+fn emit(
+    mut reader: EventReader<EmitParticles>,
+    effects: Res<Assets<ParticleEffect>>,
+    mut particles: ResMut<Particles>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::rng();
+
+    for event in reader.read() {
+        let Some(effect) = effects.get(&event.effect) else {
+            continue;
+        };
+
+        for emitter in effect.emitters.iter() {
+            for _ in 0..emitter.count {
+                let direction = Vec2::from_angle(rng.random_range(0. ..TAU)).extend(0.);
+                let speed = sample(&mut rng, emitter.speed);
+
+                let (velocity, angle, spin) = match emitter.shape {
+                    ParticleShape::Spark => (direction * speed, direction.y.atan2(direction.x), 0.),
+                    ParticleShape::Debris { spin } => (
+                        direction * speed,
+                        rng.random_range(0. ..TAU),
+                        sample(&mut rng, spin) * if rng.random() { 1. } else { -1. },
+                    ),
+                    // rings don't move, they grow
+                    ParticleShape::Ring { .. } => (Vec3::ZERO, 0., speed),
+                };
+
+                particles.0.push(Particle {
+                    shape: emitter.shape,
+                    position: event.position,
+                    velocity: velocity + event.velocity,
+                    angle,
+                    spin,
+                    size: sample(&mut rng, emitter.size),
+                    drag: emitter.drag,
+                    color: emitter.color,
+                    fade: emitter.fade,
+                    spawned: time.elapsed(),
+                    lifetime: Duration::from_secs_f32(sample(&mut rng, emitter.lifetime)),
+                });
+            }
+        }
+    }
+}
+
+fn sample(rng: &mut impl rand::Rng, (min, max): (f32, f32)) -> f32 {
+    if min < max {
+        rng.random_range(min..max)
+    } else {
+        min
+    }
+}
+
+fn simulate(mut particles: ResMut<Particles>, time: Res<Time>) {
+    let now = time.elapsed();
+    let dt = time.delta_secs();
+
+    particles.0.retain(|p| now - p.spawned < p.lifetime);
+
+    for particle in particles.0.iter_mut() {
+        particle.velocity *= (1. - particle.drag * dt).max(0.);
+        particle.position += particle.velocity * dt;
+
+        match particle.shape {
+            ParticleShape::Spark => {
+                // sparks stay aligned with their direction of travel
+                if particle.velocity != Vec3::ZERO {
+                    particle.angle = particle.velocity.y.atan2(particle.velocity.x);
+                }
+            }
+            ParticleShape::Debris { .. } => {
+                particle.angle += particle.spin * dt;
+            }
+            ParticleShape::Ring { .. } => {
+                particle.size += particle.spin * dt;
+            }
+        }
+    }
+}
+
+fn update_mesh(
+    particles: Res<Particles>,
+    mut renderer: Query<(&Mesh3d, &mut Visibility), With<ParticleRenderer>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+) {
+    let Ok((mesh, mut visibility)) = renderer.get_single_mut() else {
+        return;
+    };
+
+    if particles.0.is_empty() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    let now = time.elapsed();
+    let mut list = LineList { lines: vec![], colors: vec![] };
+
+    for particle in particles.0.iter() {
+        let age = (now - particle.spawned).as_secs_f32()
+            / particle.lifetime.as_secs_f32().max(f32::EPSILON);
+        let color = particle.color * particle.fade.brightness(age);
+
+        match particle.shape {
+            ParticleShape::Spark => {
+                // the head of a spark is at its position, the tail trails behind
+                let tail = Vec2::from_angle(particle.angle).extend(0.) * particle.size;
+                list.lines
+                    .push([particle.position, particle.position - tail]);
+                list.colors.push([color, color * 0.2]);
+            }
+            ParticleShape::Debris { .. } => {
+                let half = Vec2::from_angle(particle.angle).extend(0.) * particle.size / 2.;
+                list.lines
+                    .push([particle.position - half, particle.position + half]);
+                list.colors.push([color; 2]);
+            }
+            ParticleShape::Ring { segments } => {
+                let segments = segments.max(3);
+                let point = |i: u32| {
+                    let angle = i as f32 / segments as f32 * TAU;
+                    particle.position + Vec2::from_angle(angle).extend(0.) * particle.size
+                };
+
+                for i in 0..segments {
+                    list.lines.push([point(i), point(i + 1)]);
+                    list.colors.push([color; 2]);
+                }
+            }
+        }
+    }
+
+    meshes.insert(&mesh.0, list.into());
+    *visibility = Visibility::Inherited;
+}
@@ -0,0 +1,123 @@
+use std::f32::consts::TAU;
+
+use avian3d::prelude::*;
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use rand::Rng as _;
+
+use crate::{
+    assets::model::Model,
+    instanced_lines::InstancedLines,
+    line_material::LineList,
+    misc::{Expire, FadeCurve, FadeOut, GameLayer, LOCKED_AXES},
+    weapon::damage::FatalDamage,
+};
+
+/// Break the entity's model into its individual lines when it dies. Each line becomes a
+/// physics-driven fragment that flies outward, spins, and fades out.
+#[derive(Component, Clone)]
+pub struct Shatter {
+    pub model: Handle<Model>,
+    pub color: LinearRgba,
+    /// Outward speed of the fragments, in units per second
+    pub speed: f32,
+    /// Maximum spin of the fragments, in radians per second
+    pub spin: f32,
+    pub lifetime: Duration,
+}
+
+impl Shatter {
+    pub fn new<C: Into<LinearRgba>>(model: Handle<Model>, color: C) -> Self {
+        Self {
+            model,
+            color: color.into(),
+            speed: 15.,
+            spin: 15.,
+            lifetime: Duration::from_secs(1),
+        }
+    }
+}
+
+/// One mesh per line of a model, centered on the line, shared between all fragments.
+#[derive(Resource, Default)]
+pub struct FragmentMeshes(HashMap<(AssetId<Model>, usize), Handle<Mesh>>);
+
+#[allow(clippy::too_many_arguments)]
+pub fn shatter(
+    mut reader: EventReader<FatalDamage>,
+    targets: Query<(&Shatter, &GlobalTransform, Option<&LinearVelocity>)>,
+    models: Res<Assets<Model>>,
+    mut fragment_meshes: ResMut<FragmentMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let mut rng = rand::rng();
+
+    for FatalDamage { target, .. } in reader.read() {
+        let Ok((shatter, transform, velocity)) = targets.get(*target) else {
+            continue;
+        };
+
+        let Some(model) = models.get(&shatter.model) else {
+            warn!("shatter: model of {target:?} is not loaded");
+            continue;
+        };
+
+        let (scale, rotation, origin) = transform.to_scale_rotation_translation();
+        let inherited_velocity = velocity.map_or(Vec3::ZERO, |v| v.0);
+
+        for (index, line) in model.lines.iter().enumerate() {
+            let center = (line.start + line.end) / 2.;
+
+            let mesh = fragment_meshes
+                .0
+                .entry((shatter.model.id(), index))
+                .or_insert_with(|| {
+                    meshes.add(LineList {
+                        lines: vec![[line.start - center, line.end - center]],
+                        colors: vec![line.colors(model.default_color)],
+                    })
+                })
+                .clone();
+
+            let position = transform.transform_point(center);
+            let outward = (position - origin)
+                .with_z(0.)
+                .try_normalize()
+                .unwrap_or_else(|| Vec2::from_angle(rng.random_range(0. ..TAU)).extend(0.));
+            let speed = shatter.speed * rng.random_range(0.5..=1.);
+            let spin = shatter.spin * rng.random_range(-1. ..=1.);
+
+            commands.spawn((
+                Name::new("Fragment"),
+                InstancedLines::new(mesh, shatter.color),
+                Transform {
+                    translation: position,
+                    rotation,
+                    scale,
+                },
+                RigidBody::Dynamic,
+                LOCKED_AXES,
+                Collider::segment(line.start - center, line.end - center),
+                CollisionLayers::new(GameLayer::Default, GameLayer::MapGeometry),
+                // segments have no volume, so the collider can't provide these
+                Mass(0.1),
+                AngularInertia::new(Vec3::splat(0.01)),
+                LinearVelocity(outward * speed + inherited_velocity),
+                AngularVelocity(Vec3::Z * spin),
+                FadeOut {
+                    color: shatter.color,
+                    curve: FadeCurve::Linear,
+                    start: time.elapsed(),
+                    duration: shatter.lifetime,
+                },
+                Expire {
+                    deadline: time.elapsed() + shatter.lifetime,
+                },
+            ));
+        }
+    }
+}
@@ -62,13 +62,12 @@ pub fn update_zoom(mut query: Query<(&mut CameraOffset, &ActionState<Action>)>)
 pub fn update_target_pos(
     window: Query<&Window, With<PrimaryWindow>>,
     camera: Query<(&Camera, &GlobalTransform)>,
-    mut player_aim_target: Query<&mut Transform, With<PlayerAimTarget>>,
+    mut player_aim_targets: Query<&mut Transform, With<PlayerAimTarget>>,
 ) {
     let Ok(window) = window.get_single() else {
         return;
     };
     let (camera, camera_transform) = camera.single();
-    let mut player_aim_target = player_aim_target.single_mut();
 
     let Some(cursor_position) = window.cursor_position() else {
         return;
@@ -84,8 +83,11 @@ pub fn update_target_pos(
 
     let position = ray.get_point(distance);
 
-    if player_aim_target.translation != position {
-        player_aim_target.translation = position;
+    // every ship follows the mouse, until players get their own aiming devices
+    for mut player_aim_target in player_aim_targets.iter_mut() {
+        if player_aim_target.translation != position {
+            player_aim_target.translation = position;
+        }
     }
 }
 
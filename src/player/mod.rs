@@ -8,9 +8,15 @@ use bevy::{
 use leafwing_input_manager::{plugin::InputManagerPlugin, InputManagerBundle};
 
 use crate::{
+    assets::{
+        animation::{AnimationRepeat, ModelAnimation, REST_FRAME},
+        collider::{ModelCollider, ModelColliderShape},
+        socket::SocketModel,
+    },
     line_material::LineMaterial,
     misc::{CameraOffset, GameLayer, MovementSpeed, LOCKED_AXES},
     team::Team,
+    trail::Trail,
     utils::LookAt2d as _,
     weapon::{ActiveWeapon, Weapon},
 };
@@ -48,6 +54,10 @@ pub struct PlayerShip;
 #[derive(Component)]
 pub struct PlayerAimTarget;
 
+/// The [PlayerAimTarget] entity the ship turns towards
+#[derive(Component)]
+pub struct AimTarget(pub Entity);
+
 fn init_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -82,20 +92,32 @@ fn init_player(
         .spawn((AnimationGraphHandle(graphs.add(graph)), animation_player))
         .id();
 
+    let model = asset_server.load("models/ship.mdl.json");
+
+    let aim_target = commands
+        .spawn((Name::new("Player aim target"), Transform::default(), PlayerAimTarget))
+        .id();
+
     let mesh = commands
         .spawn((
             mesh_name,
-            Mesh3d(asset_server.load("models/ship.mdl.json")),
+            Mesh3d(asset_server.load("models/ship.mdl.json#mesh")),
             MeshMaterial3d(line_materials.add(Color::WHITE)),
             AnimationTarget {
                 id: anim_target_id,
                 player: animation,
             },
+            ModelAnimation::new(model.clone(), [REST_FRAME, "flap"], Duration::from_millis(400))
+                .with_repeat(AnimationRepeat::PingPong)
+                .with_easing(EaseFunction::SineInOut),
         ))
         .id();
 
     let weapon = commands
-        .spawn(Weapon { cooldown: Duration::from_millis(150) })
+        .spawn(Weapon {
+            cooldown: Duration::from_millis(150),
+            sockets: vec!["muzzle_left".into(), "muzzle_right".into()],
+        })
         .id();
 
     commands
@@ -106,7 +128,7 @@ fn init_player(
             Visibility::default(),
             RigidBody::Dynamic,
             LOCKED_AXES,
-            Collider::sphere(1.),
+            ModelCollider::new(model.clone(), ModelColliderShape::ConvexHull),
             Mass(10.),
             Team::Player,
             CollisionLayers::new(GameLayer::Player, GameLayer::all_bits()),
@@ -114,10 +136,11 @@ fn init_player(
             MovementSpeed { max_speed: 36., acceleration: 15. },
             CameraOffset::default(),
             ActiveWeapon(weapon),
+            Trail::new(Duration::from_millis(250), LinearRgba::rgb(3., 2., 0.))
+                .with_points([Vec3::new(-0.8, -0.5, 0.), Vec3::new(-0.8, 0.5, 0.)]),
         ))
+        .insert((SocketModel(model), AimTarget(aim_target)))
         .add_children(&[mesh, animation, weapon]);
-
-    commands.spawn((Name::new("Player aim target"), Transform::default(), PlayerAimTarget));
 }
 
 fn camera_follow_player(
@@ -134,13 +157,16 @@ fn camera_follow_player(
 }
 
 pub fn aim_player_ship(
-    mut player_ship: Query<&mut Transform, (With<PlayerShip>, Without<PlayerAimTarget>)>,
-    player_aim_target: Query<&Transform, (With<PlayerAimTarget>, Without<PlayerShip>)>,
+    mut player_ships: Query<(&mut Transform, &AimTarget), Without<PlayerAimTarget>>,
+    player_aim_targets: Query<&Transform, With<PlayerAimTarget>>,
 ) {
-    let mut player_ship = player_ship.single_mut();
-    let player_aim_target = player_aim_target.single();
+    for (mut player_ship, AimTarget(aim_target)) in player_ships.iter_mut() {
+        let Ok(player_aim_target) = player_aim_targets.get(*aim_target) else {
+            continue;
+        };
 
-    if player_ship.translation != player_aim_target.translation {
-        player_ship.look_at_2d(player_aim_target.translation);
+        if player_ship.translation != player_aim_target.translation {
+            player_ship.look_at_2d(player_aim_target.translation);
+        }
     }
 }
@@ -0,0 +1,179 @@
+//! Vector-display style afterimage trails.
+//!
+//! Every entity with a [Trail] has its recent [GlobalTransform]s recorded, which are drawn as
+//! fading line ribbons. The samples outlive their entity until they have faded, so trails don't
+//! vanish on impact. All trails are drawn as a single [LineList] mesh with one [LineMaterial].
+
+use std::collections::VecDeque;
+
+use bevy::{ecs::entity::EntityHashMap, prelude::*, utils::Duration};
+
+use crate::{
+    line_material::{LineList, LineMaterial},
+    misc::FadeCurve,
+};
+
+pub struct TrailPlugin;
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Trail>()
+            .init_resource::<Trails>()
+            .add_systems(Startup, init_renderer)
+            .add_systems(
+                PostUpdate,
+                (record_samples, update_mesh)
+                    .chain()
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Shortest [Trail::length], so fading never divides by zero
+pub const MIN_LENGTH: Duration = Duration::from_millis(1);
+
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct Trail {
+    /// How long a recorded sample stays visible
+    pub length: Duration,
+    pub fade: FadeCurve,
+    pub color: LinearRgba,
+    /// Points in the entity's local space that each leave a trail behind. Using two or more
+    /// points gives a ribbon, with a rung between the points of every sample.
+    pub points: Vec<Vec3>,
+}
+
+impl Trail {
+    /// Trails are at least [MIN_LENGTH] long
+    pub fn new<C: Into<LinearRgba>>(length: Duration, color: C) -> Self {
+        Self {
+            length: length.max(MIN_LENGTH),
+            fade: FadeCurve::default(),
+            color: color.into(),
+            points: vec![Vec3::ZERO],
+        }
+    }
+
+    pub fn with_fade(mut self, fade: FadeCurve) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    pub fn with_points(mut self, points: impl Into<Vec<Vec3>>) -> Self {
+        self.points = points.into();
+        self
+    }
+}
+
+/// Recorded samples of every trail, by the entity leaving it
+#[derive(Resource, Default)]
+pub struct Trails(EntityHashMap<TrailSamples>);
+
+struct TrailSamples {
+    trail: Trail,
+    samples: VecDeque<(Duration, GlobalTransform)>,
+}
+
+#[derive(Component)]
+struct TrailRenderer;
+
+fn init_renderer(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LineMaterial>>,
+    mut commands: Commands,
+) {
+    let mesh = meshes.add(LineList { lines: vec![], colors: vec![] });
+    // trail colours live in the vertex colours, so the material itself stays white
+    let material = materials.add(LinearRgba::WHITE);
+
+    commands.spawn((
+        Name::new("Trails"),
+        TrailRenderer,
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::default(),
+        Visibility::Hidden,
+    ));
+}
+
+fn record_samples(
+    owners: Query<(Entity, Ref<Trail>, &GlobalTransform)>,
+    mut trails: ResMut<Trails>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed();
+
+    for (owner, trail, transform) in owners.iter() {
+        let samples = trails.0.entry(owner).or_insert_with(|| TrailSamples {
+            trail: trail.clone(),
+            samples: VecDeque::new(),
+        });
+
+        // the entity id may have been reused since the last trail of it faded
+        if trail.is_added() {
+            samples.samples.clear();
+        }
+
+        samples.trail.clone_from(&trail);
+        samples.samples.push_front((now, *transform));
+    }
+
+    trails.0.retain(|_, TrailSamples { trail, samples }| {
+        while samples
+            .back()
+            .is_some_and(|(recorded, _)| now - *recorded > trail.length)
+        {
+            samples.pop_back();
+        }
+
+        !samples.is_empty()
+    });
+}
+
+fn update_mesh(
+    trails: Res<Trails>,
+    mut renderer: Query<(&Mesh3d, &mut Visibility), With<TrailRenderer>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+) {
+    let Ok((mesh, mut visibility)) = renderer.get_single_mut() else {
+        return;
+    };
+
+    let now = time.elapsed();
+    let mut list = LineList { lines: vec![], colors: vec![] };
+
+    for TrailSamples { trail, samples } in trails.0.values() {
+        let color_at = |recorded: Duration| {
+            // the length can still be edited to zero in the inspector
+            let age = (now - recorded).as_secs_f32() / trail.length.max(MIN_LENGTH).as_secs_f32();
+            trail.color * trail.fade.brightness(age)
+        };
+
+        for [(newer_time, newer), (older_time, older)] in
+            samples.iter().collect::<Vec<_>>().array_windows()
+        {
+            let (newer_color, older_color) = (color_at(*newer_time), color_at(*older_time));
+
+            for point in trail.points.iter() {
+                list.lines
+                    .push([newer.transform_point(*point), older.transform_point(*point)]);
+                list.colors.push([newer_color, older_color]);
+            }
+
+            for [a, b] in trail.points.array_windows() {
+                list.lines
+                    .push([older.transform_point(*a), older.transform_point(*b)]);
+                list.colors.push([older_color; 2]);
+            }
+        }
+    }
+
+    if list.lines.is_empty() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    meshes.insert(&mesh.0, list.into());
+    *visibility = Visibility::Inherited;
+}
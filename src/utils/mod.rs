@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+pub mod spatial_hash;
+
 pub trait LookAt2d {
     fn look_at_2d(&mut self, target: Vec3);
 }
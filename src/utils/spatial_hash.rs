@@ -0,0 +1,75 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Buckets points into square cells on the XY plane, so that neighbours can be found without
+/// comparing every pair. Queries are fastest with cells about as large as the query radius.
+#[derive(Debug)]
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Vec3, T)>>,
+}
+
+impl<T> SpatialHash<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self { cell_size, cells: default() }
+    }
+
+    /// Removes all points, and changes the cell size for the next ones
+    pub fn reset(&mut self, cell_size: f32) {
+        self.cell_size = cell_size;
+        self.cells.clear();
+    }
+
+    fn cell(&self, position: Vec3) -> IVec2 {
+        (position.xy() / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn insert(&mut self, position: Vec3, value: T) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((position, value));
+    }
+
+    /// Points within `radius` of `position` on the XY plane
+    pub fn within(&self, position: Vec3, radius: f32) -> impl Iterator<Item = &(Vec3, T)> {
+        let min = self.cell(position - Vec3::splat(radius));
+        let max = self.cell(position + Vec3::splat(radius));
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(point, _)| point.xy().distance_squared(position.xy()) <= radius * radius)
+    }
+}
+
+impl<T> Default for SpatialHash<T> {
+    fn default() -> Self {
+        Self::new(10.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbours_within_radius() {
+        let points: Vec<_> = (0..400)
+            .map(|i| Vec3::new((i % 20) as f32 * 1.7 - 15., (i / 20) as f32 * 2.3 - 20., 0.))
+            .collect();
+
+        let mut hash = SpatialHash::new(4.);
+        for (index, &point) in points.iter().enumerate() {
+            hash.insert(point, index);
+        }
+
+        for center in [Vec3::ZERO, Vec3::new(-14., 19., 0.), Vec3::new(7.3, -3.1, 5.)] {
+            let mut found: Vec<_> = hash.within(center, 5.).map(|&(_, index)| index).collect();
+            found.sort();
+
+            let expected: Vec<_> = (0..points.len())
+                .filter(|&index| points[index].xy().distance(center.xy()) <= 5.)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -6,45 +6,53 @@ use bevy::{
 };
 
 use super::damage::{Damage, DamageType, ImpactDamage};
-use crate::{line_material::LineMaterial, misc::Expire};
+use crate::{
+    assets::collider::{ModelCollider, ModelColliderShape},
+    instanced_lines::InstancedLines,
+    misc::{Expire, FadeCurve},
+    trail::Trail,
+};
 
 #[derive(Component)]
 #[component(on_add = populate)]
 pub struct Bullet;
 
+/// Speed bullets are fired at
+pub const SPEED: f32 = 50.;
+
 #[derive(Resource)]
 pub struct BulletResources {
-    mesh: Handle<Mesh>,
-    material: Handle<LineMaterial>,
+    lines: InstancedLines,
+    collider: ModelCollider,
 }
 
-pub fn init_resource(
-    asset_server: Res<AssetServer>,
-    mut materials: ResMut<Assets<LineMaterial>>,
-    mut commands: Commands,
-) {
-    let mesh = asset_server.load("models/laser.mdl.json");
-    let material = materials.add(LinearRgba::RED * 50.);
+pub fn init_resource(asset_server: Res<AssetServer>, mut commands: Commands) {
+    let mesh = asset_server.load("models/laser.mdl.json#mesh");
+    let lines = InstancedLines::new(mesh, LinearRgba::RED * 50.);
+
+    let model = asset_server.load("models/laser.mdl.json");
+    let collider = ModelCollider::new(model, ModelColliderShape::Capsules { radius: 0.1 });
 
-    commands.insert_resource(BulletResources { mesh, material: dbg!(material) });
+    commands.insert_resource(BulletResources { lines, collider });
 }
 
 pub fn populate(mut world: DeferredWorld, entity: Entity, _id: ComponentId) {
     let res = world.resource::<BulletResources>();
-    let mesh = res.mesh.clone();
-    let material = res.material.clone();
+    let lines = res.lines.clone();
+    let collider = res.collider.clone();
 
     let deadline = world.resource::<Time>().elapsed() + Duration::from_secs(5);
 
     world.commands().entity(entity).insert_if_new((
-        Mesh3d(mesh),
-        MeshMaterial3d(material),
+        lines,
         RigidBody::Kinematic,
-        Collider::segment(Vec3::ZERO, Vec3::X),
+        collider,
         ImpactDamage {
             damage: Damage { value: 10., ty: DamageType::Energy },
             despawn_on_impact: true,
         },
         Expire { deadline },
+        Trail::new(Duration::from_millis(80), LinearRgba::RED * 10.)
+            .with_fade(FadeCurve::Power(2.)),
     ));
 }
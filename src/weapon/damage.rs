@@ -1,4 +1,4 @@
-use avian3d::prelude::CollidingEntities;
+use avian3d::prelude::*;
 use bevy::prelude::*;
 
 use crate::team::Team;
@@ -23,6 +23,17 @@ pub struct ImpactDamage {
     pub despawn_on_impact: bool,
 }
 
+/// A collider of a multi-part entity, e.g. a boss. Damage to it goes to the [Health] of its rigid
+/// body, times `multiplier`. Weak points have a multiplier above 1.
+#[derive(Component, Debug)]
+pub struct Hitbox {
+    pub multiplier: f32,
+}
+
+/// Bullets bounce off this collider instead of dealing damage.
+#[derive(Component, Debug)]
+pub struct Armor;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Damage {
     pub value: f32,
@@ -61,12 +72,23 @@ pub struct FatalDamage {
 pub fn contact_damage(
     sources: Query<(Entity, &Team, &ImpactDamage, &CollidingEntities)>,
     targets: Query<(Entity, &Team), With<Health>>,
+    hitboxes: Query<(&Hitbox, &ColliderParent)>,
+    armor: Query<(), With<Armor>>,
     mut writer: EventWriter<DamageEvent>,
     mut commands: Commands,
 ) {
     for (source, source_team, impact_damage, colliding_entities) in sources.iter() {
         for ent in colliding_entities.0.iter() {
-            let Ok((target, target_team)) = targets.get(*ent) else {
+            if armor.contains(*ent) {
+                continue;
+            }
+
+            let (ent, multiplier) = match hitboxes.get(*ent) {
+                Ok((hitbox, parent)) => (parent.get(), hitbox.multiplier),
+                Err(_) => (*ent, 1.),
+            };
+
+            let Ok((target, target_team)) = targets.get(ent) else {
                 continue;
             };
 
@@ -74,7 +96,11 @@ pub fn contact_damage(
                 continue;
             }
 
-            writer.send(DamageEvent { target, damage: impact_damage.damage });
+            let damage = Damage {
+                value: impact_damage.damage.value * multiplier,
+                ..impact_damage.damage
+            };
+            writer.send(DamageEvent { target, damage });
 
             if impact_damage.despawn_on_impact {
                 trace!("despawning bullet {entity:?} after impact", entity = source);
@@ -84,6 +110,48 @@ pub fn contact_damage(
     }
 }
 
+/// Reflects bullets off [Armor] they touch. Only sources that despawn on impact count as bullets,
+/// enemies ramming into armour are left to the physics solver.
+pub fn deflect(
+    mut bullets: Query<(
+        Entity,
+        &ImpactDamage,
+        &mut LinearVelocity,
+        &mut Transform,
+        &CollidingEntities,
+    )>,
+    armor: Query<&Rotation, With<Armor>>,
+    collisions: Res<Collisions>,
+) {
+    for (bullet, impact_damage, mut velocity, mut transform, colliding_entities) in
+        bullets.iter_mut()
+    {
+        if !impact_damage.despawn_on_impact {
+            continue;
+        }
+
+        for ent in colliding_entities.0.iter() {
+            let (Ok(rotation), Some(contacts)) = (armor.get(*ent), collisions.get(*ent, bullet))
+            else {
+                continue;
+            };
+
+            for manifold in contacts.manifolds.iter() {
+                // each normal points away from its own entity
+                let normal = match contacts.entity1 == *ent {
+                    true => manifold.global_normal1(rotation),
+                    false => manifold.global_normal2(rotation),
+                };
+
+                if velocity.dot(normal) < 0. {
+                    velocity.0 = velocity.reflect(normal);
+                    transform.rotation = Quat::from_rotation_arc(Vec3::X, velocity.normalize());
+                }
+            }
+        }
+    }
+}
+
 pub fn apply_damage(
     mut reader: EventReader<DamageEvent>,
     mut health_query: Query<&mut Health>,
@@ -115,3 +183,60 @@ pub fn despawn_on_fatal_damage(mut commands: Commands, mut reader: EventReader<F
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce as _;
+
+    use super::*;
+
+    #[test]
+    fn deflect_only_bullets() {
+        let mut world = World::new();
+        let armor = world.spawn((Armor, Rotation::default())).id();
+
+        let mut spawn = |despawn_on_impact| {
+            world
+                .spawn((
+                    ImpactDamage {
+                        damage: Damage { value: 10., ty: DamageType::Impact },
+                        despawn_on_impact,
+                    },
+                    LinearVelocity(Vec3::NEG_X),
+                    Transform::default(),
+                    CollidingEntities([armor].into_iter().collect()),
+                ))
+                .id()
+        };
+        let (bullet, enemy) = (spawn(true), spawn(false));
+
+        let mut collisions = Collisions::default();
+        for ent in [bullet, enemy] {
+            collisions.insert_collision_pair(Contacts {
+                entity1: armor,
+                entity2: ent,
+                body_entity1: Some(armor),
+                body_entity2: Some(ent),
+                manifolds: vec![ContactManifold {
+                    contacts: vec![],
+                    normal1: Vec3::X,
+                    normal2: Vec3::NEG_X,
+                    index: 0,
+                }],
+                is_sensor: false,
+                during_current_frame: true,
+                during_previous_frame: false,
+                total_normal_impulse: 0.,
+                total_tangent_impulse: Vec2::ZERO,
+            });
+        }
+        world.insert_resource(collisions);
+
+        world.run_system_once(deflect).unwrap();
+
+        let velocity = |ent| world.get::<LinearVelocity>(ent).unwrap().0;
+        assert_eq!(velocity(bullet), Vec3::X);
+        assert_eq!(velocity(enemy), Vec3::NEG_X);
+        assert_eq!(world.get::<Transform>(enemy).unwrap().rotation, Quat::IDENTITY);
+    }
+}
@@ -5,7 +5,7 @@ use bevy::{
 };
 use bullet::Bullet;
 
-use crate::{misc::GameLayer, team::Team};
+use crate::{assets::socket::Sockets, misc::GameLayer, team::Team};
 
 pub mod bullet;
 pub mod damage;
@@ -24,6 +24,7 @@ impl Plugin for WeaponPlugin {
                 Update,
                 (
                     damage::contact_damage,
+                    damage::deflect,
                     damage::apply_damage,
                     damage::despawn_on_fatal_damage,
                     shoot,
@@ -48,35 +49,59 @@ pub struct ActiveWeapon(pub Entity);
 #[require(WeaponState, Transform)]
 pub struct Weapon {
     pub cooldown: Duration,
+    /// Sockets of the parent's model to fire from, in turn. Without sockets, or when the
+    /// parent has none of them, bullets spawn in front of the weapon.
+    pub sockets: Vec<String>,
 }
 
 #[derive(Component, Default)]
 pub struct WeaponState {
     pub last_used: Option<Instant>,
+    pub next_socket: usize,
 }
 
 fn shoot(
     mut events: EventReader<Shoot>,
-    mut weapons: Query<(&Weapon, &mut WeaponState, &GlobalTransform, Option<&Team>)>,
+    mut weapons: Query<(
+        &Weapon,
+        &mut WeaponState,
+        &GlobalTransform,
+        Option<&Team>,
+        Option<&Parent>,
+    )>,
+    sockets: Sockets,
     mut commands: Commands,
 ) {
     for Shoot(entity) in events.read() {
-        if let Ok((weapon, mut state, transform, team)) = weapons.get_mut(*entity) {
+        if let Ok((weapon, mut state, transform, team, parent)) = weapons.get_mut(*entity) {
             if state
                 .last_used
                 .is_none_or(|last_used| last_used.elapsed() >= weapon.cooldown)
             {
                 state.last_used = Some(Instant::now());
 
+                let socket = weapon
+                    .sockets
+                    .get(state.next_socket % weapon.sockets.len().max(1))
+                    .zip(parent)
+                    .and_then(|(name, parent)| sockets.world_transform(parent.get(), name));
+                state.next_socket = state.next_socket.wrapping_add(1);
+
+                let team = team.copied().unwrap_or_default();
+                let transform = socket.unwrap_or_else(|| {
+                    transform.mul_transform(Transform::from_translation(Vec3::X))
+                });
+
                 commands.spawn((
                     Bullet,
-                    Transform::from_translation(transform.translation() + transform.right() * 1.)
+                    team,
+                    Transform::from_translation(transform.translation())
                         .with_scale(Vec3::splat(2.))
                         .with_rotation(transform.rotation()),
-                    LinearVelocity(transform.right() * 50.),
+                    LinearVelocity(transform.right() * bullet::SPEED),
                     CollisionLayers::new(
                         GameLayer::Bullet,
-                        GameLayer::all_bits() ^ team.unwrap().game_layer().to_bits(),
+                        GameLayer::all_bits() ^ team.game_layer().to_bits(),
                     ),
                 ));
             }